@@ -0,0 +1,461 @@
+use std::fmt::{self, Write};
+
+use crate::escape::escape_char;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// Matches the empty string.
+    Empty,
+    /// Matches `text` literally.
+    Literal(String),
+    /// Pattern text taken as is from `raw!`, treated as an opaque unit.
+    Raw(String),
+    Class(Class),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat { node: Box<Node>, min: u32, max: Option<u32>, greedy: bool },
+    Group { node: Box<Node>, capturing: bool, name: Option<String> },
+    Look { node: Box<Node>, ahead: bool, negated: bool },
+    Anchor(Anchor),
+    /// Matches `node` with inline flags, rendered as `(?flags:...)`.
+    Flags { node: Box<Node>, flags: Flags },
+    /// Matches the text an earlier capture group matched, as `\k<group>`.
+    Backref(GroupRef),
+}
+
+/// Capture group a `Node::Backref` refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupRef {
+    Index(u32),
+    Name(String),
+}
+
+impl From<u32> for GroupRef {
+    fn from(index: u32) -> Self {
+        GroupRef::Index(index)
+    }
+}
+
+impl From<&str> for GroupRef {
+    fn from(name: &str) -> Self {
+        GroupRef::Name(name.to_string())
+    }
+}
+
+/// Inline flags of a `Node::Flags`. `None` keeps the surrounding setting
+/// and `Some(false)` clears a flag set further out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Flags {
+    /// `i`
+    pub case_insensitive: Option<bool>,
+    /// `m`, `^` and `$` also match at line breaks.
+    pub multi_line: Option<bool>,
+    /// `s`, `.` also matches `\n`.
+    pub dot_matches_new_line: Option<bool>,
+    /// `x`, whitespace and `#` comments in the pattern are ignored.
+    pub ignore_whitespace: Option<bool>,
+    /// `U`, quantifiers are lazy unless followed by `?`.
+    pub swap_greed: Option<bool>,
+    /// `u`, classes and case folding follow Unicode; on by default.
+    pub unicode: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Start,
+    End,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerlClass {
+    Digit,
+    Word,
+    Space,
+}
+
+/// ASCII punctuation and the typographic quotes and dashes of Russian and
+/// English text, as matched by `marks!`.
+pub const MARKS: &str = "`~!@#$%^&*()_+-={}[]|\\:;\"'<>,.?/’‘«»“”„—–…";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassItem {
+    Char(char),
+    Range(char, char),
+    Perl { class: PerlClass, negated: bool },
+    Unicode { name: String, negated: bool },
+    Class(Box<Class>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Class {
+    /// `.`, any character except a newline unless the `s` flag is set.
+    /// Inside other classes, where the flag can not reach it, it stands
+    /// for every character, newline included.
+    Any,
+    Perl { class: PerlClass, negated: bool },
+    /// Unicode property or script such as `\p{Greek}` or `\p{L}`.
+    Unicode { name: String, negated: bool },
+    /// Union of its items, rendered as `[...]`.
+    Set { items: Vec<ClassItem>, negated: bool },
+    /// Characters in both classes, rendered as `[a&&b]`.
+    Intersection(Box<Class>, Box<Class>),
+    /// Characters in the first class but not in the second, rendered as `[a--b]`.
+    Difference(Box<Class>, Box<Class>),
+}
+
+// Binding strength of a rendered node. A child is wrapped in `(?:...)` when
+// it binds weaker than its position requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Alt,
+    Concat,
+    Repeat,
+    Atom,
+}
+
+impl Node {
+    fn precedence(&self) -> Precedence {
+        match self {
+            Node::Empty => Precedence::Concat,
+            Node::Literal(text) => match text.chars().count() {
+                1 => Precedence::Atom,
+                _ => Precedence::Concat,
+            },
+            Node::Raw(_) => Precedence::Alt,
+            Node::Concat(nodes) => match nodes.as_slice() {
+                [node] => node.precedence(),
+                _ => Precedence::Concat,
+            },
+            Node::Alt(nodes) => match nodes.as_slice() {
+                [node] => node.precedence(),
+                _ => Precedence::Alt,
+            },
+            Node::Repeat { .. } => Precedence::Repeat,
+            // Assertions can not be repeated directly.
+            Node::Anchor(_) => Precedence::Concat,
+            Node::Class(_) | Node::Group { .. } | Node::Look { .. } | Node::Flags { .. } | Node::Backref(_) => {
+                Precedence::Atom
+            }
+        }
+    }
+
+    /// Writes the pattern text for this node, adding only the non-capturing
+    /// groups required to keep its structure.
+    pub fn render(&self, out: &mut String) {
+        match self {
+            Node::Empty => {}
+            Node::Literal(text) => {
+                for c in text.chars() {
+                    push_escaped(out, c);
+                }
+            }
+            Node::Raw(source) => out.push_str(source),
+            Node::Class(class) => class.render(out),
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    node.render_at(Precedence::Concat, out);
+                }
+            }
+            Node::Alt(nodes) => {
+                if nodes.is_empty() {
+                    // An alternation without branches never matches.
+                    out.push_str(r"[^\s\S]");
+                }
+                for (i, node) in nodes.iter().enumerate() {
+                    if i > 0 {
+                        out.push('|');
+                    }
+                    node.render(out);
+                }
+            }
+            Node::Repeat { node, min, max, greedy } => {
+                node.render_at(Precedence::Atom, out);
+                match (min, max) {
+                    (0, Some(1)) => out.push('?'),
+                    (0, None) => out.push('*'),
+                    (1, None) => out.push('+'),
+                    (min, Some(max)) if min == max => write!(out, "{{{}}}", min).unwrap(),
+                    (min, Some(max)) => write!(out, "{{{},{}}}", min, max).unwrap(),
+                    (min, None) => write!(out, "{{{},}}", min).unwrap(),
+                }
+                if !greedy {
+                    out.push('?');
+                }
+            }
+            Node::Group { node, capturing, name } => {
+                match (capturing, name) {
+                    (_, Some(name)) => write!(out, "(?P<{}>", name).unwrap(),
+                    (true, None) => out.push('('),
+                    (false, None) => out.push_str("(?:"),
+                }
+                node.render(out);
+                out.push(')');
+            }
+            Node::Look { node, ahead, negated } => {
+                out.push_str(match (ahead, negated) {
+                    (true, false) => "(?=",
+                    (true, true) => "(?!",
+                    (false, false) => "(?<=",
+                    (false, true) => "(?<!",
+                });
+                node.render(out);
+                out.push(')');
+            }
+            Node::Anchor(anchor) => out.push_str(match anchor {
+                Anchor::Start => "^",
+                Anchor::End => "$",
+                Anchor::WordBoundary => r"\b",
+                Anchor::NotWordBoundary => r"\B",
+            }),
+            Node::Flags { node, flags } => {
+                out.push_str("(?");
+                flags.render(out);
+                out.push(':');
+                node.render(out);
+                out.push(')');
+            }
+            // `\k<1>` rather than `\1`, which a following digit would extend.
+            Node::Backref(GroupRef::Index(index)) => write!(out, "\\k<{}>", index).unwrap(),
+            Node::Backref(GroupRef::Name(name)) => write!(out, "\\k<{}>", name).unwrap(),
+        }
+    }
+
+    /// Whether a lookahead or lookbehind occurs anywhere in the tree.
+    /// Text inside `Raw` nodes is not inspected.
+    pub fn has_lookaround(&self) -> bool {
+        match self {
+            Node::Look { .. } => true,
+            Node::Concat(nodes) | Node::Alt(nodes) => nodes.iter().any(Node::has_lookaround),
+            Node::Repeat { node, .. } | Node::Group { node, .. } | Node::Flags { node, .. } => node.has_lookaround(),
+            Node::Empty | Node::Literal(_) | Node::Raw(_) | Node::Class(_) | Node::Anchor(_) => false,
+            Node::Backref(_) => false,
+        }
+    }
+
+    /// Whether a backreference occurs anywhere in the tree, including
+    /// inside lookaround.
+    pub fn has_backref(&self) -> bool {
+        let mut found = false;
+        self.for_each(&mut |node| found |= matches!(node, Node::Backref(_)));
+        found
+    }
+
+    /// Calls `visit` on this node and every node below it, parents before
+    /// children and in pattern order, so capture groups are met in the
+    /// order they are numbered.
+    pub fn for_each(&self, visit: &mut dyn FnMut(&Node)) {
+        visit(self);
+        match self {
+            Node::Concat(nodes) | Node::Alt(nodes) => nodes.iter().for_each(|node| node.for_each(visit)),
+            Node::Repeat { node, .. } | Node::Group { node, .. } | Node::Look { node, .. } | Node::Flags { node, .. } => {
+                node.for_each(visit)
+            }
+            Node::Empty | Node::Literal(_) | Node::Raw(_) | Node::Class(_) | Node::Anchor(_) | Node::Backref(_) => {}
+        }
+    }
+
+    /// The construct that only the `fancy` backtracking engine can run,
+    /// if the tree has one.
+    pub fn fancy_construct(&self) -> Option<&'static str> {
+        if self.has_lookaround() {
+            Some("lookaround")
+        } else if self.has_backref() {
+            Some("a backreference")
+        } else {
+            None
+        }
+    }
+
+    fn render_at(&self, required: Precedence, out: &mut String) {
+        if self.precedence() < required {
+            out.push_str("(?:");
+            self.render(out);
+            out.push(')');
+        } else {
+            self.render(out);
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        self.render(&mut out);
+        f.write_str(&out)
+    }
+}
+
+impl Flags {
+    fn letters(&self) -> [(char, Option<bool>); 6] {
+        [
+            ('i', self.case_insensitive),
+            ('m', self.multi_line),
+            ('s', self.dot_matches_new_line),
+            ('x', self.ignore_whitespace),
+            ('U', self.swap_greed),
+            ('u', self.unicode),
+        ]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters().iter().all(|(_, on)| on.is_none())
+    }
+
+    /// These flags with the ones set in `inner` taking precedence.
+    pub fn then(self, inner: Flags) -> Flags {
+        Flags {
+            case_insensitive: inner.case_insensitive.or(self.case_insensitive),
+            multi_line: inner.multi_line.or(self.multi_line),
+            dot_matches_new_line: inner.dot_matches_new_line.or(self.dot_matches_new_line),
+            ignore_whitespace: inner.ignore_whitespace.or(self.ignore_whitespace),
+            swap_greed: inner.swap_greed.or(self.swap_greed),
+            unicode: inner.unicode.or(self.unicode),
+        }
+    }
+
+    /// Writes the flag letters, e.g. `is-U`.
+    pub fn render(&self, out: &mut String) {
+        let letters = self.letters();
+        out.extend(letters.iter().filter(|(_, on)| *on == Some(true)).map(|(letter, _)| letter));
+        if letters.iter().any(|(_, on)| *on == Some(false)) {
+            out.push('-');
+            out.extend(letters.iter().filter(|(_, on)| *on == Some(false)).map(|(letter, _)| letter));
+        }
+    }
+}
+
+impl PerlClass {
+    fn render(self, negated: bool, out: &mut String) {
+        out.push_str(match (self, negated) {
+            (PerlClass::Digit, false) => r"\d",
+            (PerlClass::Digit, true) => r"\D",
+            (PerlClass::Word, false) => r"\w",
+            (PerlClass::Word, true) => r"\W",
+            (PerlClass::Space, false) => r"\s",
+            (PerlClass::Space, true) => r"\S",
+        });
+    }
+}
+
+impl Class {
+    pub fn negate(self) -> Class {
+        match self {
+            // Every character, as within other classes, so nothing is left.
+            Class::Any => Class::Set { items: Vec::new(), negated: false },
+            Class::Perl { class, negated } => Class::Perl { class, negated: !negated },
+            Class::Unicode { name, negated } => Class::Unicode { name, negated: !negated },
+            Class::Set { items, negated } => Class::Set { items, negated: !negated },
+            class => Class::Set { items: vec![ClassItem::Class(Box::new(class))], negated: true },
+        }
+    }
+
+    /// Characters in any of `classes`.
+    pub fn union<I: IntoIterator<Item = Class>>(classes: I) -> Class {
+        let mut items = Vec::new();
+        for class in classes {
+            match class {
+                Class::Set { items: nested, negated: false } => items.extend(nested),
+                Class::Perl { class, negated } => items.push(ClassItem::Perl { class, negated }),
+                Class::Unicode { name, negated } => items.push(ClassItem::Unicode { name, negated }),
+                class => items.push(ClassItem::Class(Box::new(class))),
+            }
+        }
+        match items.as_slice() {
+            [ClassItem::Perl { class, negated }] => Class::Perl { class: *class, negated: *negated },
+            [ClassItem::Unicode { name, negated }] => Class::Unicode { name: name.clone(), negated: *negated },
+            [ClassItem::Class(_)] => match items.pop() {
+                Some(ClassItem::Class(class)) => *class,
+                _ => unreachable!(),
+            },
+            _ => Class::Set { items, negated: false },
+        }
+    }
+
+    pub fn intersection(self, other: Class) -> Class {
+        Class::Intersection(Box::new(self), Box::new(other))
+    }
+
+    pub fn difference(self, other: Class) -> Class {
+        Class::Difference(Box::new(self), Box::new(other))
+    }
+
+    pub fn render(&self, out: &mut String) {
+        match self {
+            Class::Any => out.push('.'),
+            Class::Perl { class, negated } => class.render(*negated, out),
+            Class::Unicode { name, negated } => render_unicode(name, *negated, out),
+            Class::Set { items, negated } => {
+                if items.is_empty() {
+                    // `[]` is not valid syntax, spell out the empty or full set.
+                    out.push_str(if *negated { r"[\s\S]" } else { r"[^\s\S]" });
+                    return;
+                }
+                out.push('[');
+                if *negated {
+                    out.push('^');
+                }
+                for item in items {
+                    item.render(out);
+                }
+                out.push(']');
+            }
+            Class::Intersection(left, right) => render_operation(left, "&&", right, out),
+            Class::Difference(left, right) => render_operation(left, "--", right, out),
+        }
+    }
+
+    // Renders the class where it appears nested inside brackets.
+    fn render_nested(&self, out: &mut String) {
+        match self {
+            Class::Any => out.push_str(r"[\s\S]"),
+            class => class.render(out),
+        }
+    }
+}
+
+impl ClassItem {
+    fn render(&self, out: &mut String) {
+        match self {
+            ClassItem::Char(c) => push_class_char(out, *c),
+            ClassItem::Range(from, to) => {
+                push_class_char(out, *from);
+                out.push('-');
+                push_class_char(out, *to);
+            }
+            ClassItem::Perl { class, negated } => class.render(*negated, out),
+            ClassItem::Unicode { name, negated } => render_unicode(name, *negated, out),
+            ClassItem::Class(class) if **class == Class::Any => out.push_str(r"\s\S"),
+            ClassItem::Class(class) => class.render_nested(out),
+        }
+    }
+}
+
+fn render_unicode(name: &str, negated: bool, out: &mut String) {
+    write!(out, "\\{}{{{}}}", if negated { 'P' } else { 'p' }, name).unwrap();
+}
+
+fn render_operation(left: &Class, operator: &str, right: &Class, out: &mut String) {
+    out.push('[');
+    left.render_nested(out);
+    out.push_str(operator);
+    right.render_nested(out);
+    out.push(']');
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    let (buffer, len) = escape_char(c);
+    out.push_str(std::str::from_utf8(&buffer[..len]).unwrap());
+}
+
+// Inside brackets only the characters with a meaning there need escaping,
+// plus `#` and whitespace, which verbose mode would take for a comment or
+// skip.
+fn push_class_char(out: &mut String, c: char) {
+    if matches!(c, '\\' | '[' | ']' | '^' | '-' | '&' | '~' | '#') || c.is_whitespace() {
+        push_escaped(out, c);
+    } else {
+        out.push(c);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::ast::{Anchor, Class, ClassItem, Flags, GroupRef, Node, PerlClass, MARKS};
+
+/// What a combinator macro takes and the function that builds its node.
+#[derive(Clone, Copy)]
+pub enum Combinator {
+    /// `digit!()`
+    Leaf(fn() -> Node),
+    /// `escape!("a.b")`
+    Text(fn(&str) -> Node),
+    /// `one_or_more!(part)`
+    Part(fn(Node) -> Node),
+    /// `sequence!(part, ...)`, at least one part.
+    Parts(fn(Vec<Node>) -> Node),
+    /// `named_group!("name", part)`
+    Named(fn(&str, Node) -> Node),
+    /// `at_least!(part, min)`
+    Count(fn(Node, u32) -> Node),
+    /// `between!(part, min, max)`, failing if `min > max`.
+    Range(fn(Node, u32, u32) -> Result<Node, String>),
+    /// `backref!(1)` or `backref!("name")`
+    Backref(fn(GroupRef) -> Node),
+    /// `char_class![item, ...]`, at least one class.
+    Classes(fn(Vec<Class>) -> Node),
+    /// `not_class!(class)`
+    Class(fn(Class) -> Node),
+    /// `class_difference!(left, right)`
+    ClassPair(fn(Class, Class) -> Node),
+}
+
+impl Combinator {
+    /// The number of arguments, or `None` if it takes one or more.
+    pub fn arity(self) -> Option<usize> {
+        match self {
+            Combinator::Leaf(_) => Some(0),
+            Combinator::Text(_) | Combinator::Part(_) | Combinator::Backref(_) | Combinator::Class(_) => Some(1),
+            Combinator::Named(_) | Combinator::Count(_) | Combinator::ClassPair(_) => Some(2),
+            Combinator::Range(_) => Some(3),
+            Combinator::Parts(_) | Combinator::Classes(_) => None,
+        }
+    }
+}
+
+/// Every combinator macro by name. The macros of RegexLib call these
+/// functions and `regex_const!` looks them up here, so both build the
+/// same tree. `times!` is listed once per arity.
+pub const COMBINATORS: &[(&str, Combinator)] = &[
+    ("raw", Combinator::Text(raw)),
+    ("escape", Combinator::Text(escape)),
+    ("non_capturing_group", Combinator::Part(non_capturing_group)),
+    ("capturing_group", Combinator::Part(capturing_group)),
+    ("named_group", Combinator::Named(named_group)),
+    ("one_or_more", Combinator::Part(one_or_more)),
+    ("zero_or_more", Combinator::Part(zero_or_more)),
+    ("optional", Combinator::Part(optional)),
+    ("lazy", Combinator::Part(zero_or_more_lazy)),
+    ("one_or_more_lazy", Combinator::Part(one_or_more_lazy)),
+    ("zero_or_more_lazy", Combinator::Part(zero_or_more_lazy)),
+    ("optional_lazy", Combinator::Part(optional_lazy)),
+    ("times", Combinator::Count(at_least)),
+    ("times", Combinator::Range(between)),
+    ("exactly", Combinator::Count(exactly)),
+    ("at_least", Combinator::Count(at_least)),
+    ("at_least_lazy", Combinator::Count(at_least_lazy)),
+    ("between", Combinator::Range(between)),
+    ("between_lazy", Combinator::Range(between_lazy)),
+    ("any_of", Combinator::Parts(any_of)),
+    ("sequence", Combinator::Parts(sequence)),
+    ("not", Combinator::Part(not_followed_by)),
+    ("not_followed_by", Combinator::Part(not_followed_by)),
+    ("followed_by", Combinator::Part(followed_by)),
+    ("preceded_by", Combinator::Part(preceded_by)),
+    ("not_preceded_by", Combinator::Part(not_preceded_by)),
+    ("not_escaped", Combinator::Part(not_escaped)),
+    ("backref", Combinator::Backref(backref)),
+    ("case_insensitive", Combinator::Part(case_insensitive)),
+    ("case_sensitive", Combinator::Part(case_sensitive)),
+    ("multiline", Combinator::Part(multiline)),
+    ("dot_matches_newline", Combinator::Part(dot_matches_newline)),
+    ("ignore_whitespace", Combinator::Part(ignore_whitespace)),
+    ("swap_greed", Combinator::Part(swap_greed)),
+    ("ascii_only", Combinator::Part(ascii_only)),
+    ("custom_word_boundary", Combinator::Leaf(custom_word_boundary)),
+    ("anything", Combinator::Leaf(anything)),
+    ("nothing", Combinator::Leaf(nothing)),
+    ("word_boundary", Combinator::Leaf(word_boundary)),
+    ("begin", Combinator::Leaf(begin)),
+    ("end", Combinator::Leaf(end)),
+    ("whitespace", Combinator::Leaf(whitespace)),
+    ("alpha_numeric", Combinator::Leaf(alpha_numeric)),
+    ("non_alpha_numeric", Combinator::Leaf(non_alpha_numeric)),
+    ("digit", Combinator::Leaf(digit)),
+    ("non_digit", Combinator::Leaf(non_digit)),
+    ("cyrillic_letter", Combinator::Leaf(cyrillic_letter)),
+    ("punctuation", Combinator::Leaf(punctuation)),
+    ("marks", Combinator::Leaf(marks)),
+    ("char_class", Combinator::Classes(char_class)),
+    ("not_class", Combinator::Class(not_class)),
+    ("class_intersection", Combinator::ClassPair(class_intersection)),
+    ("class_difference", Combinator::ClassPair(class_difference)),
+    ("unicode_class", Combinator::Text(unicode_class)),
+];
+
+/// The combinator called `name` with `args` arguments.
+pub fn lookup(name: &str, args: usize) -> Option<Combinator> {
+    let named: Vec<Combinator> =
+        COMBINATORS.iter().filter(|(other, _)| *other == name).map(|(_, combinator)| *combinator).collect();
+    // If none takes `args` arguments, the first one reports the mismatch.
+    named
+        .iter()
+        .copied()
+        .find(|combinator| combinator.arity().is_none_or(|arity| arity == args))
+        .or(named.first().copied())
+}
+
+/// The class a `char_class!` item stands for: a class, or a literal of a
+/// single character. Anything else is given back.
+pub fn as_class(node: Node) -> Result<Class, Node> {
+    match node {
+        Node::Class(class) => Ok(class),
+        Node::Literal(text) if text.chars().count() == 1 => {
+            Ok(Class::Set { items: text.chars().map(ClassItem::Char).collect(), negated: false })
+        }
+        node => Err(node),
+    }
+}
+
+/// `node{min,max}`, failing if `max` is less than `min`.
+pub fn repeat(node: Node, min: u32, max: Option<u32>, greedy: bool) -> Result<Node, String> {
+    match max {
+        Some(max) if max < min => Err(format!("repetition minimum {} exceeds its maximum {}", min, max)),
+        _ => Ok(Node::Repeat { node: Box::new(node), min, max, greedy }),
+    }
+}
+
+// A repetition whose bounds are known to be in order.
+fn quantify(node: Node, min: u32, max: Option<u32>, greedy: bool) -> Node {
+    Node::Repeat { node: Box::new(node), min, max, greedy }
+}
+
+pub fn raw(source: &str) -> Node {
+    Node::Raw(source.to_string())
+}
+
+pub fn escape(text: &str) -> Node {
+    Node::Literal(text.to_string())
+}
+
+pub fn group(node: Node, capturing: bool, name: Option<&str>) -> Node {
+    Node::Group { node: Box::new(node), capturing, name: name.map(str::to_string) }
+}
+
+pub fn non_capturing_group(node: Node) -> Node {
+    group(node, false, None)
+}
+
+pub fn capturing_group(node: Node) -> Node {
+    group(node, true, None)
+}
+
+pub fn named_group(name: &str, node: Node) -> Node {
+    group(node, true, Some(name))
+}
+
+pub fn one_or_more(node: Node) -> Node {
+    quantify(node, 1, None, true)
+}
+
+pub fn zero_or_more(node: Node) -> Node {
+    quantify(node, 0, None, true)
+}
+
+pub fn optional(node: Node) -> Node {
+    quantify(node, 0, Some(1), true)
+}
+
+pub fn one_or_more_lazy(node: Node) -> Node {
+    quantify(node, 1, None, false)
+}
+
+pub fn zero_or_more_lazy(node: Node) -> Node {
+    quantify(node, 0, None, false)
+}
+
+pub fn optional_lazy(node: Node) -> Node {
+    quantify(node, 0, Some(1), false)
+}
+
+pub fn exactly(node: Node, count: u32) -> Node {
+    quantify(node, count, Some(count), true)
+}
+
+pub fn at_least(node: Node, min: u32) -> Node {
+    quantify(node, min, None, true)
+}
+
+pub fn at_least_lazy(node: Node, min: u32) -> Node {
+    quantify(node, min, None, false)
+}
+
+pub fn between(node: Node, min: u32, max: u32) -> Result<Node, String> {
+    repeat(node, min, Some(max), true)
+}
+
+pub fn between_lazy(node: Node, min: u32, max: u32) -> Result<Node, String> {
+    repeat(node, min, Some(max), false)
+}
+
+pub fn any_of(nodes: Vec<Node>) -> Node {
+    Node::Alt(nodes)
+}
+
+pub fn sequence(nodes: Vec<Node>) -> Node {
+    Node::Concat(nodes)
+}
+
+pub fn look(node: Node, ahead: bool, negated: bool) -> Node {
+    Node::Look { node: Box::new(node), ahead, negated }
+}
+
+pub fn followed_by(node: Node) -> Node {
+    look(node, true, false)
+}
+
+pub fn not_followed_by(node: Node) -> Node {
+    look(node, true, true)
+}
+
+pub fn preceded_by(node: Node) -> Node {
+    look(node, false, false)
+}
+
+pub fn not_preceded_by(node: Node) -> Node {
+    look(node, false, true)
+}
+
+pub fn not_escaped(node: Node) -> Node {
+    sequence(vec![not_preceded_by(escape("\\")), node])
+}
+
+pub fn backref(group: GroupRef) -> Node {
+    Node::Backref(group)
+}
+
+pub fn with_flags(node: Node, flags: Flags) -> Node {
+    Node::Flags { node: Box::new(node), flags }
+}
+
+pub fn case_insensitive(node: Node) -> Node {
+    with_flags(node, Flags { case_insensitive: Some(true), ..Flags::default() })
+}
+
+pub fn case_sensitive(node: Node) -> Node {
+    with_flags(node, Flags { case_insensitive: Some(false), ..Flags::default() })
+}
+
+pub fn multiline(node: Node) -> Node {
+    with_flags(node, Flags { multi_line: Some(true), ..Flags::default() })
+}
+
+pub fn dot_matches_newline(node: Node) -> Node {
+    with_flags(node, Flags { dot_matches_new_line: Some(true), ..Flags::default() })
+}
+
+pub fn ignore_whitespace(node: Node) -> Node {
+    with_flags(node, Flags { ignore_whitespace: Some(true), ..Flags::default() })
+}
+
+pub fn swap_greed(node: Node) -> Node {
+    with_flags(node, Flags { swap_greed: Some(true), ..Flags::default() })
+}
+
+pub fn ascii_only(node: Node) -> Node {
+    with_flags(node, Flags { unicode: Some(false), ..Flags::default() })
+}
+
+pub fn custom_word_boundary() -> Node {
+    let separators = Class::union([Class::Perl { class: PerlClass::Space, negated: false }, marks_class()]);
+    any_of(vec![one_or_more(Node::Class(separators)), begin(), end()])
+}
+
+pub fn anything() -> Node {
+    Node::Class(Class::Any)
+}
+
+pub fn nothing() -> Node {
+    Node::Empty
+}
+
+pub fn word_boundary() -> Node {
+    Node::Anchor(Anchor::WordBoundary)
+}
+
+pub fn begin() -> Node {
+    Node::Anchor(Anchor::Start)
+}
+
+pub fn end() -> Node {
+    Node::Anchor(Anchor::End)
+}
+
+fn perl(class: PerlClass, negated: bool) -> Node {
+    Node::Class(Class::Perl { class, negated })
+}
+
+pub fn whitespace() -> Node {
+    perl(PerlClass::Space, false)
+}
+
+pub fn alpha_numeric() -> Node {
+    perl(PerlClass::Word, false)
+}
+
+pub fn non_alpha_numeric() -> Node {
+    perl(PerlClass::Word, true)
+}
+
+pub fn digit() -> Node {
+    perl(PerlClass::Digit, false)
+}
+
+pub fn non_digit() -> Node {
+    perl(PerlClass::Digit, true)
+}
+
+pub fn cyrillic_letter() -> Node {
+    Node::Class(Class::Set {
+        items: vec![
+            ClassItem::Range('а', 'я'),
+            ClassItem::Range('А', 'Я'),
+            ClassItem::Char('ё'),
+            ClassItem::Char('Ё'),
+        ],
+        negated: false,
+    })
+}
+
+pub fn punctuation() -> Node {
+    Node::Class(Class::Set {
+        items: ['.', ',', '!', '?', ';', ':'].into_iter().map(ClassItem::Char).collect(),
+        negated: false,
+    })
+}
+
+fn marks_class() -> Class {
+    Class::Set { items: MARKS.chars().map(ClassItem::Char).collect(), negated: false }
+}
+
+pub fn marks() -> Node {
+    Node::Class(marks_class())
+}
+
+pub fn char_class(classes: Vec<Class>) -> Node {
+    Node::Class(Class::union(classes))
+}
+
+pub fn not_class(class: Class) -> Node {
+    Node::Class(class.negate())
+}
+
+pub fn class_intersection(left: Class, right: Class) -> Node {
+    Node::Class(left.intersection(right))
+}
+
+pub fn class_difference(left: Class, right: Class) -> Node {
+    Node::Class(left.difference(right))
+}
+
+pub fn unicode_class(name: &str) -> Node {
+    Node::Class(Class::Unicode { name: name.to_string(), negated: false })
+}
@@ -0,0 +1,145 @@
+/// Escapes `text` so that it matches itself literally.
+///
+/// Follows `regex::escape` for metacharacters (`\.+*?()|[]{}^$#&-~`) and
+/// additionally escapes whitespace, so the result stays literal inside
+/// `(?x)` verbose groups as well.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        let (buffer, len) = escape_char(c);
+        // `escape_char` only emits ASCII escapes or the UTF-8 encoding of `c`.
+        escaped.push_str(std::str::from_utf8(&buffer[..len]).unwrap());
+    }
+    escaped
+}
+
+const fn is_meta(c: char) -> bool {
+    matches!(
+        c,
+        '\\' | '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' | '#' | '&' | '-' | '~'
+    )
+}
+
+// Everything `char::is_whitespace` accepts, which is what verbose mode skips.
+const fn is_whitespace(c: char) -> bool {
+    matches!(
+        c,
+        '\u{9}'..='\u{D}'
+            | ' '
+            | '\u{85}'
+            | '\u{A0}'
+            | '\u{1680}'
+            | '\u{2000}'..='\u{200A}'
+            | '\u{2028}'
+            | '\u{2029}'
+            | '\u{202F}'
+            | '\u{205F}'
+            | '\u{3000}'
+    )
+}
+
+const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Escaped form of a single character, as bytes and their count.
+#[doc(hidden)]
+pub const fn escape_char(c: char) -> ([u8; 10], usize) {
+    let mut buffer = [0u8; 10];
+    let short = match c {
+        '\t' => b't',
+        '\n' => b'n',
+        '\r' => b'r',
+        '\u{B}' => b'v',
+        '\u{C}' => b'f',
+        ' ' => b' ',
+        _ if is_meta(c) => c as u8,
+        _ => 0,
+    };
+    if short != 0 {
+        buffer[0] = b'\\';
+        buffer[1] = short;
+        return (buffer, 2);
+    }
+    if is_whitespace(c) {
+        let code = c as u32;
+        buffer[0] = b'\\';
+        buffer[1] = b'x';
+        buffer[2] = b'{';
+        let mut digits = 1;
+        while code >> (4 * digits) != 0 {
+            digits += 1;
+        }
+        let mut i = 0;
+        while i < digits {
+            buffer[3 + i] = HEX[((code >> (4 * (digits - 1 - i))) & 0xF) as usize];
+            i += 1;
+        }
+        buffer[3 + digits] = b'}';
+        return (buffer, 4 + digits);
+    }
+    let len = c.len_utf8();
+    let mut utf8 = [0u8; 4];
+    c.encode_utf8(&mut utf8);
+    let mut i = 0;
+    while i < len {
+        buffer[i] = utf8[i];
+        i += 1;
+    }
+    (buffer, len)
+}
+
+const fn decode_char(bytes: &[u8], at: usize) -> (char, usize) {
+    let first = bytes[at] as u32;
+    let (mut code, len) = if first < 0x80 {
+        (first, 1)
+    } else if first < 0xE0 {
+        (first & 0x1F, 2)
+    } else if first < 0xF0 {
+        (first & 0x0F, 3)
+    } else {
+        (first & 0x07, 4)
+    };
+    let mut i = 1;
+    while i < len {
+        code = (code << 6) | (bytes[at + i] as u32 & 0x3F);
+        i += 1;
+    }
+    match char::from_u32(code) {
+        Some(c) => (c, len),
+        None => panic!("invalid UTF-8"),
+    }
+}
+
+/// Length in bytes of `escape(text)`.
+#[doc(hidden)]
+pub const fn escaped_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut at = 0;
+    let mut len = 0;
+    while at < bytes.len() {
+        let (c, width) = decode_char(bytes, at);
+        len += escape_char(c).1;
+        at += width;
+    }
+    len
+}
+
+/// `escape(text)` computed in a const context; `N` must be `escaped_len(text)`.
+#[doc(hidden)]
+pub const fn escape_bytes<const N: usize>(text: &str) -> [u8; N] {
+    let bytes = text.as_bytes();
+    let mut escaped = [0u8; N];
+    let mut at = 0;
+    let mut len = 0;
+    while at < bytes.len() {
+        let (c, width) = decode_char(bytes, at);
+        let (buffer, count) = escape_char(c);
+        let mut i = 0;
+        while i < count {
+            escaped[len + i] = buffer[i];
+            i += 1;
+        }
+        len += count;
+        at += width;
+    }
+    escaped
+}
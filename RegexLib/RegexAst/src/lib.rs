@@ -0,0 +1,9 @@
+#![allow(non_snake_case)]
+
+//! Syntax tree, combinators and escaping shared by RegexLib and its
+//! RegexConst proc-macro, so both build and render identical patterns.
+//! Use it through `RegexLib::ast` and `RegexLib::escape`.
+
+pub mod ast;
+pub mod combinators;
+pub mod escape;
@@ -0,0 +1,271 @@
+use proc_macro::{Delimiter, Span, TokenTree};
+
+use RegexAst::ast::{Class, ClassItem, GroupRef, Node};
+use RegexAst::combinators::{self, Combinator};
+
+pub struct Error {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Error { span, message: message.into() }
+    }
+}
+
+/// A combinator invocation together with the invocations nested in it, so
+/// validation errors can be traced back to their source.
+pub struct Evaluated {
+    pub node: Node,
+    pub name: String,
+    pub span: Span,
+    pub children: Vec<Evaluated>,
+}
+
+/// Evaluates one combinator expression, e.g. `sequence!(digit!(), escape!("."))`.
+pub fn eval(tokens: &[TokenTree], outer: Span) -> Result<Evaluated, Error> {
+    let tokens = flatten(tokens);
+    let (name, span, args) = match tokens.as_slice() {
+        [] => return Err(Error::new(outer, "expected a combinator such as `digit!()`")),
+        [path @ .., TokenTree::Ident(name), TokenTree::Punct(bang), TokenTree::Group(args)]
+            if bang.as_char() == '!' && is_path_prefix(path) =>
+        {
+            (name.to_string(), name.span(), split_args(args.stream().into_iter().collect()))
+        }
+        [first, ..] => {
+            return Err(Error::new(
+                first.span(),
+                "only combinator macros and literals can be evaluated at compile time",
+            ))
+        }
+    };
+    let call = Call { name: &name, span, args: &args };
+    let mut children = Vec::new();
+    let node = call.node(&mut children)?;
+    Ok(Evaluated { node, name, span, children })
+}
+
+struct Call<'a> {
+    name: &'a str,
+    span: Span,
+    args: &'a [Vec<TokenTree>],
+}
+
+impl Call<'_> {
+    fn node(&self, children: &mut Vec<Evaluated>) -> Result<Node, Error> {
+        let Some(combinator) = combinators::lookup(self.name, self.args.len()) else {
+            return Err(self.error(format!("`{}!` is not a combinator known to `regex_const!`", self.name)));
+        };
+        if let Some(arity) = combinator.arity() {
+            self.arity(arity)?;
+        } else if self.args.is_empty() {
+            let what = if matches!(combinator, Combinator::Classes(_)) { "item" } else { "part" };
+            return Err(self.error(format!("`{}!` needs at least one {}", self.name, what)));
+        }
+        let node = match combinator {
+            Combinator::Leaf(build) => build(),
+            Combinator::Text(build) => build(&self.string(0)?),
+            Combinator::Part(build) => build(self.part(0, children)?),
+            Combinator::Parts(build) => build(self.parts(children)?),
+            Combinator::Named(build) => {
+                let name = self.string(0)?;
+                build(&name, self.part(1, children)?)
+            }
+            Combinator::Count(build) => {
+                let count = self.integer(1)?;
+                build(self.part(0, children)?, count)
+            }
+            Combinator::Range(build) => {
+                let (min, max) = (self.integer(1)?, self.integer(2)?);
+                build(self.part(0, children)?, min, max).map_err(|message| self.error(message))?
+            }
+            Combinator::Backref(build) => {
+                let (span, text) = self.literal(0)?;
+                match (unquote(&text), self.integer(0)) {
+                    (Some(name), _) => build(GroupRef::Name(name)),
+                    (None, Ok(index)) => build(GroupRef::Index(index)),
+                    (None, Err(_)) => return Err(Error::new(span, "expected a group number or name")),
+                }
+            }
+            Combinator::Classes(build) => {
+                build((0..self.args.len()).map(|index| self.class(index, children)).collect::<Result<_, _>>()?)
+            }
+            Combinator::Class(build) => build(self.class(0, children)?),
+            Combinator::ClassPair(build) => build(self.class(0, children)?, self.class(1, children)?),
+        };
+        Ok(node)
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::new(self.span, message)
+    }
+
+    fn arity(&self, expected: usize) -> Result<(), Error> {
+        if self.args.len() == expected {
+            Ok(())
+        } else {
+            Err(self.error(format!(
+                "`{}!` takes {} argument(s), found {}",
+                self.name,
+                expected,
+                self.args.len()
+            )))
+        }
+    }
+
+    fn part(&self, index: usize, children: &mut Vec<Evaluated>) -> Result<Node, Error> {
+        let child = eval(&self.args[index], self.span)?;
+        let node = child.node.clone();
+        children.push(child);
+        Ok(node)
+    }
+
+    fn parts(&self, children: &mut Vec<Evaluated>) -> Result<Vec<Node>, Error> {
+        (0..self.args.len()).map(|index| self.part(index, children)).collect()
+    }
+
+    // An item of `char_class!`: a char, a char range, a string or a class combinator.
+    fn class(&self, index: usize, children: &mut Vec<Evaluated>) -> Result<Class, Error> {
+        let tokens = flatten(&self.args[index]);
+        let item = match tokens.as_slice() {
+            [TokenTree::Literal(literal)] => {
+                let text = literal.to_string();
+                if let Some(c) = unquote_char(&text) {
+                    ClassItem::Char(c)
+                } else if let Some(chars) = unquote(&text) {
+                    return Ok(Class::Set { items: chars.chars().map(ClassItem::Char).collect(), negated: false });
+                } else {
+                    return Err(Error::new(literal.span(), "expected a char, string or class"));
+                }
+            }
+            [TokenTree::Literal(from), TokenTree::Punct(first), TokenTree::Punct(second), TokenTree::Punct(third), TokenTree::Literal(to)]
+                if [first.as_char(), second.as_char(), third.as_char()] == ['.', '.', '='] =>
+            {
+                match (unquote_char(&from.to_string()), unquote_char(&to.to_string())) {
+                    (Some(from), Some(to)) if from <= to => ClassItem::Range(from, to),
+                    (Some(from), Some(to)) => {
+                        return Err(Error::new(self.span, format!("empty character range {:?}..={:?}", from, to)))
+                    }
+                    _ => return Err(Error::new(from.span(), "expected a range of char literals")),
+                }
+            }
+            _ => {
+                let child = eval(&tokens, self.span)?;
+                let Ok(class) = combinators::as_class(child.node.clone()) else {
+                    return Err(Error::new(child.span, format!("`{}!` is not a character class", child.name)));
+                };
+                children.push(child);
+                return Ok(class);
+            }
+        };
+        Ok(Class::Set { items: vec![item], negated: false })
+    }
+
+    fn literal(&self, index: usize) -> Result<(Span, String), Error> {
+        match flatten(&self.args[index]).as_slice() {
+            [TokenTree::Literal(literal)] => Ok((literal.span(), literal.to_string())),
+            [first, ..] => Err(Error::new(first.span(), "expected a literal")),
+            [] => Err(self.error("expected a literal")),
+        }
+    }
+
+    fn string(&self, index: usize) -> Result<String, Error> {
+        let (span, text) = self.literal(index)?;
+        unquote(&text).ok_or_else(|| Error::new(span, "expected a string literal"))
+    }
+
+    fn integer(&self, index: usize) -> Result<u32, Error> {
+        let (span, text) = self.literal(index)?;
+        let digits = text.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '_');
+        digits
+            .replace('_', "")
+            .parse()
+            .map_err(|_| Error::new(span, "expected an integer literal"))
+    }
+}
+
+// Tokens forwarded through `macro_rules!` may arrive wrapped in invisible groups.
+fn flatten(tokens: &[TokenTree]) -> Vec<TokenTree> {
+    match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::None => {
+            flatten(&group.stream().into_iter().collect::<Vec<_>>())
+        }
+        _ => tokens.to_vec(),
+    }
+}
+
+// `RegexLib::digit!()` and `digit!()` are both accepted.
+fn is_path_prefix(tokens: &[TokenTree]) -> bool {
+    tokens.iter().all(|token| match token {
+        TokenTree::Ident(_) => true,
+        TokenTree::Punct(punct) => punct.as_char() == ':',
+        _ => false,
+    })
+}
+
+fn split_args(tokens: Vec<TokenTree>) -> Vec<Vec<TokenTree>> {
+    let mut args = vec![Vec::new()];
+    for token in tokens {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => args.push(Vec::new()),
+            _ => args.last_mut().unwrap().push(token),
+        }
+    }
+    if args.last().is_some_and(Vec::is_empty) {
+        args.pop();
+    }
+    args
+}
+
+// Decodes the source text of a char literal.
+fn unquote_char(literal: &str) -> Option<char> {
+    let body = literal.strip_prefix('\'')?.strip_suffix('\'')?;
+    let text = unquote(&format!("\"{}\"", body))?;
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+// Decodes the source text of a (raw) string literal.
+fn unquote(literal: &str) -> Option<String> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let body = raw.get(hashes + 1..raw.len().checked_sub(hashes + 1)?)?;
+        return Some(body.to_string());
+    }
+    let body = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut text = String::new();
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => text.push('\n'),
+            'r' => text.push('\r'),
+            't' => text.push('\t'),
+            '0' => text.push('\0'),
+            '\\' => text.push('\\'),
+            '\'' => text.push('\''),
+            '"' => text.push('"'),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                text.push(char::from(u8::from_str_radix(&hex, 16).ok()?));
+            }
+            'u' => {
+                chars.next()?;
+                let hex: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                text.push(char::from_u32(u32::from_str_radix(&hex.replace('_', ""), 16).ok()?)?);
+            }
+            '\n' => {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            }
+            _ => return None,
+        }
+    }
+    Some(text)
+}
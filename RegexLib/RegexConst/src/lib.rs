@@ -0,0 +1,150 @@
+#![allow(non_snake_case)]
+
+//! Compile-time front-end for the RegexLib combinators, re-exported as
+//! `RegexLib::regex_const!`.
+
+extern crate proc_macro;
+
+mod eval;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+// The renderer is shared with RegexLib so both produce identical patterns.
+use RegexAst::ast::{GroupRef, Node};
+use eval::{Error, Evaluated};
+
+/// Evaluates a combinator expression at compile time, validates the
+/// resulting pattern and expands to a `&'static RegexLib::ConstRegex`.
+#[proc_macro]
+pub fn regex_const(input: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    match eval::eval(&tokens, Span::call_site()).and_then(validate) {
+        Ok((pattern, fancy)) => format!(
+            "{{ static REGEX: ::RegexLib::ConstRegex = ::RegexLib::ConstRegex::new({:?}, {:?}); &REGEX }}",
+            pattern, fancy
+        )
+        .parse()
+        .unwrap(),
+        Err(error) => compile_error(error),
+    }
+}
+
+fn validate(evaluated: Evaluated) -> Result<(String, Option<&'static str>), Error> {
+    if let Some(group) = missing_group(&evaluated.node) {
+        let culprit = culprit(&evaluated, &|node| contains(node, &Node::Backref(group.clone())));
+        let group = match &group {
+            GroupRef::Index(index) => index.to_string(),
+            GroupRef::Name(name) => format!("{:?}", name),
+        };
+        return Err(Error::new(
+            culprit.span,
+            format!("`{}!` refers to capture group {}, which does not exist", culprit.name, group),
+        ));
+    }
+    let fancy = evaluated.node.fancy_construct();
+    if let Some(construct) = fancy.filter(|_| !cfg!(feature = "fancy")) {
+        let culprit = culprit(&evaluated, &|node| node.fancy_construct() == Some(construct));
+        return Err(Error::new(
+            culprit.span,
+            format!("`{}!` uses {}, which requires the `fancy` feature of RegexLib", culprit.name, construct),
+        ));
+    }
+    let pattern = evaluated.node.to_string();
+    match parse(&evaluated.node) {
+        Ok(()) => Ok((pattern, fancy)),
+        Err(error) => {
+            let culprit = culprit(&evaluated, &|node| parse(node).is_err());
+            Err(Error::new(
+                culprit.span,
+                format!("`{}!` produces an invalid regex: {}", culprit.name, error),
+            ))
+        }
+    }
+}
+
+// `regex-syntax` knows neither lookaround nor backreferences, so only the
+// pattern around them is checked.
+fn parse(node: &Node) -> Result<(), String> {
+    match regex_syntax::Parser::new().parse(&without_fancy(node).to_string()) {
+        Ok(_) => Ok(()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+fn without_fancy(node: &Node) -> Node {
+    match node {
+        Node::Look { .. } | Node::Backref(_) => Node::Empty,
+        Node::Concat(nodes) => Node::Concat(nodes.iter().map(without_fancy).collect()),
+        Node::Alt(nodes) => Node::Alt(nodes.iter().map(without_fancy).collect()),
+        Node::Repeat { node, min, max, greedy } => {
+            Node::Repeat { node: Box::new(without_fancy(node)), min: *min, max: *max, greedy: *greedy }
+        }
+        Node::Group { node, capturing, name } => {
+            Node::Group { node: Box::new(without_fancy(node)), capturing: *capturing, name: name.clone() }
+        }
+        Node::Flags { node, flags } => Node::Flags { node: Box::new(without_fancy(node)), flags: *flags },
+        node => node.clone(),
+    }
+}
+
+// The first backreference to a capture group the pattern does not have.
+fn missing_group(node: &Node) -> Option<GroupRef> {
+    let mut names = Vec::new();
+    node.for_each(&mut |node| match node {
+        Node::Group { capturing: false, name: None, .. } => {}
+        Node::Group { name, .. } => names.push(name.clone()),
+        _ => {}
+    });
+    let mut missing = None;
+    node.for_each(&mut |node| match node {
+        Node::Backref(GroupRef::Index(index)) if *index == 0 || *index as usize > names.len() => {
+            missing = missing.take().or(Some(GroupRef::Index(*index)))
+        }
+        Node::Backref(GroupRef::Name(name)) if !names.contains(&Some(name.clone())) => {
+            missing = missing.take().or(Some(GroupRef::Name(name.clone())))
+        }
+        _ => {}
+    });
+    missing
+}
+
+fn contains(node: &Node, target: &Node) -> bool {
+    let mut found = false;
+    node.for_each(&mut |node| found |= node == target);
+    found
+}
+
+// The innermost combinator whose own pattern already shows the problem.
+fn culprit<'a>(evaluated: &'a Evaluated, faulty: &dyn Fn(&Node) -> bool) -> &'a Evaluated {
+    evaluated
+        .children
+        .iter()
+        .find(|child| faulty(&child.node))
+        .map_or(evaluated, |child| culprit(child, faulty))
+}
+
+fn compile_error(error: Error) -> TokenStream {
+    let span = error.span;
+    let mut message = Literal::string(&error.message);
+    message.set_span(span);
+    let tokens = [
+        TokenTree::Punct(with_span(Punct::new(':', Spacing::Joint), span)),
+        TokenTree::Punct(with_span(Punct::new(':', Spacing::Alone), span)),
+        TokenTree::Ident(Ident::new("core", span)),
+        TokenTree::Punct(with_span(Punct::new(':', Spacing::Joint), span)),
+        TokenTree::Punct(with_span(Punct::new(':', Spacing::Alone), span)),
+        TokenTree::Ident(Ident::new("compile_error", span)),
+        TokenTree::Punct(with_span(Punct::new('!', Spacing::Alone), span)),
+        TokenTree::Group({
+            let mut group = Group::new(Delimiter::Brace, TokenTree::Literal(message).into());
+            group.set_span(span);
+            group
+        }),
+    ];
+    tokens.into_iter().collect()
+}
+
+fn with_span(mut punct: Punct, span: Span) -> Punct {
+    punct.set_span(span);
+    punct
+}
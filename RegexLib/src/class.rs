@@ -0,0 +1,55 @@
+use std::ops::RangeInclusive;
+
+use crate::ast::{Class, ClassItem};
+use crate::{combinators, RegexPart};
+
+/// Anything `char_class!` accepts as an item.
+pub trait IntoClass {
+    fn into_class(self) -> Class;
+}
+
+impl IntoClass for Class {
+    fn into_class(self) -> Class {
+        self
+    }
+}
+
+impl IntoClass for char {
+    fn into_class(self) -> Class {
+        Class::Set { items: vec![ClassItem::Char(self)], negated: false }
+    }
+}
+
+impl IntoClass for RangeInclusive<char> {
+    /// # Panics
+    ///
+    /// If the range is empty.
+    fn into_class(self) -> Class {
+        let (from, to) = self.into_inner();
+        assert!(from <= to, "empty character range {:?}..={:?}", from, to);
+        Class::Set { items: vec![ClassItem::Range(from, to)], negated: false }
+    }
+}
+
+impl IntoClass for &str {
+    /// Every character of the string.
+    fn into_class(self) -> Class {
+        Class::Set { items: self.chars().map(ClassItem::Char).collect(), negated: false }
+    }
+}
+
+impl IntoClass for RegexPart {
+    /// # Panics
+    ///
+    /// If the part is not a character class, e.g. `escape!("ab")`.
+    fn into_class(self) -> Class {
+        // A single escaped character is as good as a class of one.
+        combinators::as_class(self.into_node()).unwrap_or_else(|node| panic!("`{}` is not a character class", node))
+    }
+}
+
+impl IntoClass for &RegexPart {
+    fn into_class(self) -> Class {
+        self.clone().into_class()
+    }
+}
@@ -0,0 +1,664 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, Index, Range};
+use std::sync::{Arc, OnceLock};
+
+use regex::{Regex, RegexBuilder};
+
+use crate::ast::Flags;
+use crate::RegexPart;
+
+#[derive(Debug, Clone)]
+pub enum BuildError {
+    Regex(regex::Error),
+    #[cfg(feature = "fancy")]
+    Fancy(Box<fancy_regex::Error>),
+    /// The pattern uses a construct the enabled backends can not execute.
+    Unsupported(&'static str),
+    /// `CompileOptions::nest_limit` was set for a pattern with this
+    /// construct, but the backtracking engine has no such limit.
+    #[cfg(feature = "fancy")]
+    NestLimit(&'static str),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::Regex(error) => write!(f, "invalid regex: {}", error),
+            #[cfg(feature = "fancy")]
+            BuildError::Fancy(error) => write!(f, "invalid regex: {}", error),
+            BuildError::Unsupported(construct) => {
+                write!(f, "{} requires the `fancy` feature of RegexLib", construct)
+            }
+            #[cfg(feature = "fancy")]
+            BuildError::NestLimit(construct) => {
+                write!(f, "`nest_limit` is not supported for patterns with {}", construct)
+            }
+        }
+    }
+}
+
+impl Error for BuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BuildError::Regex(error) => Some(error),
+            #[cfg(feature = "fancy")]
+            BuildError::Fancy(error) => Some(&**error),
+            BuildError::Unsupported(_) => None,
+            #[cfg(feature = "fancy")]
+            BuildError::NestLimit(_) => None,
+        }
+    }
+}
+
+impl From<regex::Error> for BuildError {
+    fn from(error: regex::Error) -> Self {
+        BuildError::Regex(error)
+    }
+}
+
+#[cfg(feature = "fancy")]
+impl From<fancy_regex::Error> for BuildError {
+    fn from(error: fancy_regex::Error) -> Self {
+        BuildError::Fancy(Box::new(error))
+    }
+}
+
+/// The backtracking engine gave up on a haystack, for example after
+/// hitting its backtrack limit. Returned by the `try_` methods of
+/// `CompiledRegex`; without the `fancy` feature no search can fail.
+#[derive(Debug, Clone)]
+pub enum SearchError {
+    #[cfg(feature = "fancy")]
+    Fancy(Box<fancy_regex::Error>),
+}
+
+impl fmt::Display for SearchError {
+    // Without the `fancy` feature there is nothing to format.
+    #[cfg_attr(not(feature = "fancy"), allow(unused_variables))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            #[cfg(feature = "fancy")]
+            SearchError::Fancy(ref error) => write!(f, "search failed: {}", error),
+        }
+    }
+}
+
+impl Error for SearchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            #[cfg(feature = "fancy")]
+            SearchError::Fancy(ref error) => Some(&**error),
+        }
+    }
+}
+
+#[cfg(feature = "fancy")]
+impl From<fancy_regex::Error> for SearchError {
+    fn from(error: fancy_regex::Error) -> Self {
+        SearchError::Fancy(Box::new(error))
+    }
+}
+
+// Patterns with lookaround or backreferences run on `fancy-regex`,
+// everything else on `regex`.
+#[derive(Debug, Clone)]
+enum Engine {
+    Regex(Regex),
+    #[cfg(feature = "fancy")]
+    Fancy(fancy_regex::Regex),
+}
+
+/// Matcher built from a `RegexPart`.
+///
+/// Patterns that use lookaround or backreferences need the `fancy` feature
+/// and are executed by a backtracking engine. Should it give up on a
+/// haystack (for example after hitting its backtrack limit), the search
+/// methods treat that haystack as not matching, and iterators stop there.
+/// `try_is_match`, `try_find`, `try_captures` and `try_replace_all` report
+/// it as a `SearchError` instead.
+#[derive(Debug, Clone)]
+pub struct CompiledRegex {
+    engine: Engine,
+    names: Arc<[Option<String>]>,
+}
+
+impl CompiledRegex {
+    fn new(engine: Engine) -> Self {
+        let names = match &engine {
+            Engine::Regex(regex) => regex.capture_names().map(|name| name.map(str::to_string)).collect(),
+            #[cfg(feature = "fancy")]
+            Engine::Fancy(regex) => regex.capture_names().map(|name| name.map(str::to_string)).collect(),
+        };
+        CompiledRegex { engine, names }
+    }
+
+    // Compiles pattern text, on the backtracking engine if it uses a
+    // construct only that engine supports.
+    fn from_source(source: &str, fancy: Option<&'static str>, options: &CompileOptions) -> Result<Self, BuildError> {
+        match fancy {
+            None => Ok(CompiledRegex::new(Engine::Regex(options.regex_builder(source).build()?))),
+            #[cfg(feature = "fancy")]
+            Some(construct) if options.nest_limit.is_some() => Err(BuildError::NestLimit(construct)),
+            #[cfg(feature = "fancy")]
+            Some(_) => Ok(CompiledRegex::new(Engine::Fancy(options.fancy_builder(source).build()?))),
+            #[cfg(not(feature = "fancy"))]
+            Some(construct) => Err(BuildError::Unsupported(construct)),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.engine {
+            Engine::Regex(regex) => regex.as_str(),
+            #[cfg(feature = "fancy")]
+            Engine::Fancy(regex) => regex.as_str(),
+        }
+    }
+
+    /// Returns `false` if the backtracking engine gives up; use
+    /// `try_is_match` to see that error.
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.try_is_match(haystack).unwrap_or(false)
+    }
+
+    pub fn try_is_match(&self, haystack: &str) -> Result<bool, SearchError> {
+        match &self.engine {
+            Engine::Regex(regex) => Ok(regex.is_match(haystack)),
+            #[cfg(feature = "fancy")]
+            Engine::Fancy(regex) => Ok(regex.is_match(haystack)?),
+        }
+    }
+
+    /// Returns `None` if the backtracking engine gives up; use `try_find`
+    /// to see that error.
+    pub fn find<'h>(&self, haystack: &'h str) -> Option<Match<'h>> {
+        self.try_find(haystack).ok().flatten()
+    }
+
+    pub fn try_find<'h>(&self, haystack: &'h str) -> Result<Option<Match<'h>>, SearchError> {
+        match &self.engine {
+            Engine::Regex(regex) => Ok(regex.find(haystack).map(Match::from)),
+            #[cfg(feature = "fancy")]
+            Engine::Fancy(regex) => Ok(regex.find(haystack)?.map(Match::from)),
+        }
+    }
+
+    /// Iterates over the matches, stopping for good at the first haystack
+    /// position the backtracking engine gives up on. The error itself is
+    /// swallowed; `try_find` reports it.
+    pub fn find_iter<'r, 'h>(&'r self, haystack: &'h str) -> Matches<'r, 'h> {
+        let inner = match &self.engine {
+            Engine::Regex(regex) => MatchesInner::Regex(regex.find_iter(haystack)),
+            #[cfg(feature = "fancy")]
+            Engine::Fancy(regex) => MatchesInner::Fancy(regex.find_iter(haystack)),
+        };
+        Matches { inner }
+    }
+
+    /// Returns `None` if the backtracking engine gives up; use
+    /// `try_captures` to see that error.
+    pub fn captures<'h>(&self, haystack: &'h str) -> Option<Captures<'h>> {
+        self.try_captures(haystack).ok().flatten()
+    }
+
+    pub fn try_captures<'h>(&self, haystack: &'h str) -> Result<Option<Captures<'h>>, SearchError> {
+        match &self.engine {
+            Engine::Regex(regex) => Ok(regex
+                .captures(haystack)
+                .map(|captures| self.wrap_captures(haystack, captures.iter().map(|group| group.map(|m| m.range()))))),
+            #[cfg(feature = "fancy")]
+            Engine::Fancy(regex) => Ok(regex
+                .captures(haystack)?
+                .map(|captures| self.wrap_captures(haystack, captures.iter().map(|group| group.map(|m| m.range()))))),
+        }
+    }
+
+    /// Like `find_iter`, stops for good at the first error, which is
+    /// swallowed.
+    pub fn captures_iter<'r, 'h>(&'r self, haystack: &'h str) -> impl Iterator<Item = Captures<'h>> + 'r where
+        'h: 'r,
+    {
+        let captures: Box<dyn Iterator<Item = Captures<'h>> + 'r> = match &self.engine {
+            Engine::Regex(regex) => Box::new(regex.captures_iter(haystack).map(move |captures| {
+                self.wrap_captures(haystack, captures.iter().map(|group| group.map(|m| m.range())))
+            })),
+            #[cfg(feature = "fancy")]
+            Engine::Fancy(regex) => Box::new(regex.captures_iter(haystack).map_while(Result::ok).fuse().map(move |captures| {
+                self.wrap_captures(haystack, captures.iter().map(|group| group.map(|m| m.range())))
+            })),
+        };
+        captures
+    }
+
+    /// Replaces every match with `replacement`, expanding `$1` and `$name`
+    /// references to capture groups. Returns `haystack` unchanged if the
+    /// backtracking engine gives up; use `try_replace_all` to see that error.
+    pub fn replace_all<'h>(&self, haystack: &'h str, replacement: &str) -> Cow<'h, str> {
+        self.try_replace_all(haystack, replacement).unwrap_or(Cow::Borrowed(haystack))
+    }
+
+    pub fn try_replace_all<'h>(&self, haystack: &'h str, replacement: &str) -> Result<Cow<'h, str>, SearchError> {
+        match &self.engine {
+            Engine::Regex(regex) => Ok(regex.replace_all(haystack, replacement)),
+            #[cfg(feature = "fancy")]
+            Engine::Fancy(regex) => Ok(regex.try_replacen(haystack, 0, replacement)?),
+        }
+    }
+
+    /// Replaces every match with the string computed from its captures.
+    /// Matches are taken from `captures_iter`, so replacing stops at the
+    /// first error of the backtracking engine, keeping the rest unchanged.
+    pub fn replace_all_with<'h, F>(&self, haystack: &'h str, mut replacement: F) -> Cow<'h, str> where
+        F: FnMut(&Captures<'h>) -> String,
+    {
+        let mut result = String::new();
+        let mut last = 0;
+        let mut replaced = false;
+        for captures in self.captures_iter(haystack) {
+            let whole = captures.get(0).unwrap();
+            result.push_str(&haystack[last..whole.start()]);
+            result.push_str(&replacement(&captures));
+            last = whole.end();
+            replaced = true;
+        }
+        if !replaced {
+            return Cow::Borrowed(haystack);
+        }
+        result.push_str(&haystack[last..]);
+        Cow::Owned(result)
+    }
+
+    /// Splits `haystack` by the matches of this regex. Should the
+    /// backtracking engine give up, the rest of `haystack` is the last piece.
+    pub fn split<'r, 'h>(&'r self, haystack: &'h str) -> Split<'r, 'h> {
+        Split { haystack, matches: self.find_iter(haystack), last: 0, finished: false }
+    }
+
+    fn wrap_captures<'h, I>(&self, haystack: &'h str, spans: I) -> Captures<'h> where
+        I: Iterator<Item = Option<Range<usize>>>,
+    {
+        Captures { haystack, spans: spans.collect(), names: Arc::clone(&self.names) }
+    }
+}
+
+impl fmt::Display for CompiledRegex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'h> {
+    text: &'h str,
+    start: usize,
+    end: usize,
+}
+
+impl<'h> Match<'h> {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn as_str(&self) -> &'h str {
+        self.text
+    }
+}
+
+impl<'h> From<regex::Match<'h>> for Match<'h> {
+    fn from(m: regex::Match<'h>) -> Self {
+        Match { text: m.as_str(), start: m.start(), end: m.end() }
+    }
+}
+
+#[cfg(feature = "fancy")]
+impl<'h> From<fancy_regex::Match<'h>> for Match<'h> {
+    fn from(m: fancy_regex::Match<'h>) -> Self {
+        Match { text: m.as_str(), start: m.start(), end: m.end() }
+    }
+}
+
+/// Capture groups of a single match.
+#[derive(Debug, Clone)]
+pub struct Captures<'h> {
+    haystack: &'h str,
+    spans: Vec<Option<Range<usize>>>,
+    names: Arc<[Option<String>]>,
+}
+
+impl<'h> Captures<'h> {
+    pub fn get(&self, index: usize) -> Option<Match<'h>> {
+        let span = self.spans.get(index)?.clone()?;
+        Some(Match { text: &self.haystack[span.clone()], start: span.start, end: span.end })
+    }
+
+    pub fn name(&self, name: &str) -> Option<Match<'h>> {
+        let index = self.names.iter().position(|group| group.as_deref() == Some(name))?;
+        self.get(index)
+    }
+
+    /// Number of groups, including the implicit group 0 for the whole match.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+}
+
+impl<'h> Index<usize> for Captures<'h> {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &str {
+        self.get(index)
+            .unwrap_or_else(|| panic!("no group at index '{}'", index))
+            .as_str()
+    }
+}
+
+impl<'h> Index<&str> for Captures<'h> {
+    type Output = str;
+
+    fn index(&self, name: &str) -> &str {
+        self.name(name)
+            .unwrap_or_else(|| panic!("no group named '{}'", name))
+            .as_str()
+    }
+}
+
+/// Iterator over successive non-overlapping matches.
+pub struct Matches<'r, 'h> {
+    inner: MatchesInner<'r, 'h>,
+}
+
+enum MatchesInner<'r, 'h> {
+    Regex(regex::Matches<'r, 'h>),
+    #[cfg(feature = "fancy")]
+    Fancy(fancy_regex::Matches<'r, 'h>),
+    // The backtracking engine gave up; retrying would fail the same way.
+    #[cfg(feature = "fancy")]
+    Failed,
+}
+
+impl<'r, 'h> Iterator for Matches<'r, 'h> {
+    type Item = Match<'h>;
+
+    fn next(&mut self) -> Option<Match<'h>> {
+        match &mut self.inner {
+            MatchesInner::Regex(matches) => matches.next().map(Match::from),
+            #[cfg(feature = "fancy")]
+            MatchesInner::Fancy(matches) => match matches.next()? {
+                Ok(m) => Some(Match::from(m)),
+                Err(_) => {
+                    self.inner = MatchesInner::Failed;
+                    None
+                }
+            },
+            #[cfg(feature = "fancy")]
+            MatchesInner::Failed => None,
+        }
+    }
+}
+
+/// Iterator over the substrings between matches.
+pub struct Split<'r, 'h> {
+    haystack: &'h str,
+    matches: Matches<'r, 'h>,
+    last: usize,
+    finished: bool,
+}
+
+impl<'r, 'h> Iterator for Split<'r, 'h> {
+    type Item = &'h str;
+
+    fn next(&mut self) -> Option<&'h str> {
+        if self.finished {
+            return None;
+        }
+        match self.matches.next() {
+            Some(m) => {
+                let piece = &self.haystack[self.last..m.start()];
+                self.last = m.end();
+                Some(piece)
+            }
+            None => {
+                self.finished = true;
+                Some(&self.haystack[self.last..])
+            }
+        }
+    }
+}
+
+/// Settings for a whole pattern, forwarded to `regex::RegexBuilder` by
+/// `RegexPart::compile_with`. Flags set on parts take precedence over the
+/// flags set here.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// let options = CompileOptions::new().case_insensitive(true).multi_line(true);
+/// let heading = sequence!(begin!(), escape!("# "), one_or_more!(anything!()));
+/// let heading = heading.compile_with(&options).unwrap();
+/// assert_eq!(heading.find_iter("text\n# One\n# TWO").count(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    flags: Flags,
+    size_limit: Option<usize>,
+    dfa_size_limit: Option<usize>,
+    nest_limit: Option<u32>,
+    backtrack_limit: Option<usize>,
+}
+
+impl CompileOptions {
+    pub fn new() -> Self {
+        CompileOptions::default()
+    }
+
+    pub fn case_insensitive(mut self, yes: bool) -> Self {
+        self.flags.case_insensitive = Some(yes);
+        self
+    }
+
+    pub fn multi_line(mut self, yes: bool) -> Self {
+        self.flags.multi_line = Some(yes);
+        self
+    }
+
+    pub fn dot_matches_new_line(mut self, yes: bool) -> Self {
+        self.flags.dot_matches_new_line = Some(yes);
+        self
+    }
+
+    pub fn ignore_whitespace(mut self, yes: bool) -> Self {
+        self.flags.ignore_whitespace = Some(yes);
+        self
+    }
+
+    pub fn swap_greed(mut self, yes: bool) -> Self {
+        self.flags.swap_greed = Some(yes);
+        self
+    }
+
+    pub fn unicode(mut self, yes: bool) -> Self {
+        self.flags.unicode = Some(yes);
+        self
+    }
+
+    /// Approximate size limit in bytes of the compiled program.
+    pub fn size_limit(mut self, bytes: usize) -> Self {
+        self.size_limit = Some(bytes);
+        self
+    }
+
+    /// Approximate size limit in bytes of the lazy DFA cache.
+    pub fn dfa_size_limit(mut self, bytes: usize) -> Self {
+        self.dfa_size_limit = Some(bytes);
+        self
+    }
+
+    /// Deepest nesting of groups and repetitions the parser accepts.
+    /// Patterns with lookaround or backreferences can not be limited and
+    /// fail with `BuildError::NestLimit`.
+    pub fn nest_limit(mut self, depth: u32) -> Self {
+        self.nest_limit = Some(depth);
+        self
+    }
+
+    /// Steps the lookaround engine takes before giving up on a haystack.
+    pub fn backtrack_limit(mut self, steps: usize) -> Self {
+        self.backtrack_limit = Some(steps);
+        self
+    }
+
+    fn regex_builder(&self, source: &str) -> RegexBuilder {
+        let mut builder = RegexBuilder::new(source);
+        let flags = &self.flags;
+        if let Some(yes) = flags.case_insensitive {
+            builder.case_insensitive(yes);
+        }
+        if let Some(yes) = flags.multi_line {
+            builder.multi_line(yes);
+        }
+        if let Some(yes) = flags.dot_matches_new_line {
+            builder.dot_matches_new_line(yes);
+        }
+        if let Some(yes) = flags.ignore_whitespace {
+            builder.ignore_whitespace(yes);
+        }
+        if let Some(yes) = flags.swap_greed {
+            builder.swap_greed(yes);
+        }
+        if let Some(yes) = flags.unicode {
+            builder.unicode(yes);
+        }
+        if let Some(bytes) = self.size_limit {
+            builder.size_limit(bytes);
+        }
+        if let Some(bytes) = self.dfa_size_limit {
+            builder.dfa_size_limit(bytes);
+        }
+        if let Some(depth) = self.nest_limit {
+            builder.nest_limit(depth);
+        }
+        builder
+    }
+
+    // The backtracking engine only takes case insensitivity as an option,
+    // so all flags go into a leading `(?flags)` group.
+    #[cfg(feature = "fancy")]
+    fn fancy_builder(&self, source: &str) -> fancy_regex::RegexBuilder {
+        let mut pattern = String::new();
+        if !self.flags.is_empty() {
+            pattern.push_str("(?");
+            self.flags.render(&mut pattern);
+            pattern.push(')');
+        }
+        pattern.push_str(source);
+        let mut builder = fancy_regex::RegexBuilder::new(&pattern);
+        if let Some(bytes) = self.size_limit {
+            builder.delegate_size_limit(bytes);
+        }
+        if let Some(bytes) = self.dfa_size_limit {
+            builder.delegate_dfa_size_limit(bytes);
+        }
+        if let Some(steps) = self.backtrack_limit {
+            builder.backtrack_limit(steps);
+        }
+        builder
+    }
+}
+
+impl RegexPart {
+    /// Compiles the pattern. Lookaround and backreferences are rejected
+    /// with `BuildError::Unsupported` unless the `fancy` feature is enabled.
+    pub fn compile(&self) -> Result<CompiledRegex, BuildError> {
+        self.compile_with(&CompileOptions::default())
+    }
+
+    pub fn compile_with(&self, options: &CompileOptions) -> Result<CompiledRegex, BuildError> {
+        CompiledRegex::from_source(&self.source(), self.node().fancy_construct(), options)
+    }
+}
+
+/// Pattern produced by `regex_const!`, already validated at compile time and
+/// compiled on first use.
+#[derive(Debug)]
+pub struct ConstRegex {
+    source: &'static str,
+    fancy: Option<&'static str>,
+    compiled: OnceLock<CompiledRegex>,
+}
+
+impl ConstRegex {
+    #[doc(hidden)]
+    pub const fn new(source: &'static str, fancy: Option<&'static str>) -> Self {
+        ConstRegex { source, fancy, compiled: OnceLock::new() }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.source
+    }
+
+    pub fn regex(&self) -> &CompiledRegex {
+        self.compiled.get_or_init(|| {
+            CompiledRegex::from_source(self.source, self.fancy, &CompileOptions::default())
+                .unwrap_or_else(|error| panic!("regex_const! pattern failed to compile: {}", error))
+        })
+    }
+}
+
+impl Deref for ConstRegex {
+    type Target = CompiledRegex;
+
+    fn deref(&self) -> &CompiledRegex {
+        self.regex()
+    }
+}
+
+/// Compiles a combinator expression once and hands out a `&'static`
+/// reference to it. Panics on first use if the pattern is invalid.
+///
+/// ```
+/// use RegexLib::{digit, one_or_more, static_regex};
+///
+/// static_regex!(static ref NUMBER = one_or_more!(digit!()););
+///
+/// assert!(NUMBER.is_match("42"));
+/// assert!(static_regex!(one_or_more!(digit!())).is_match("7"));
+/// ```
+#[macro_export]
+macro_rules! static_regex {
+    ($(#[$attr:meta])* $(pub $(($($restriction:tt)+))?)? static ref $name:ident = $part:expr;) => {
+        $crate::lazy_static::lazy_static! {
+            $(#[$attr])* $(pub $(($($restriction)+))?)? static ref $name: $crate::CompiledRegex = $part
+                .compile()
+                .unwrap_or_else(|error| panic!("static_regex! `{}`: {}", stringify!($name), error));
+        }
+    };
+    ($part:expr) => {{
+        $crate::lazy_static::lazy_static! {
+            static ref REGEX: $crate::CompiledRegex = $part
+                .compile()
+                .unwrap_or_else(|error| panic!("static_regex!: {}", error));
+        }
+        &*REGEX
+    }};
+}
@@ -0,0 +1,202 @@
+use crate::ast::{Anchor, Class, ClassItem, Flags, GroupRef, Node, PerlClass};
+use crate::RegexPart;
+
+impl RegexPart {
+    /// Describes the pattern in English, one construct per line and nested
+    /// parts indented below their parent.
+    ///
+    /// ```
+    /// use RegexLib::*;
+    ///
+    /// let version = sequence!(
+    ///     named_group!("major", one_or_more!(digit!())),
+    ///     escape!("."),
+    ///     capturing_group!(one_or_more!(digit!()))
+    /// );
+    /// assert_eq!(
+    ///     version.explain(),
+    ///     "a sequence of:\n\
+    ///     \x20 capture group 1 \"major\": one or more of: a digit\n\
+    ///     \x20 the character \".\"\n\
+    ///     \x20 capture group 2: one or more of: a digit\n"
+    /// );
+    /// ```
+    pub fn explain(&self) -> String {
+        let mut groups = 0;
+        let mut text = String::new();
+        for line in describe(self.node(), Flags::default(), &mut groups) {
+            text.push_str(&line);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+// Capture groups are numbered by their opening parenthesis, which is the
+// order this traversal visits them in. `flags` are the ones in effect.
+fn describe(node: &Node, flags: Flags, groups: &mut u32) -> Vec<String> {
+    match node {
+        Node::Empty => vec!["the empty string".to_string()],
+        Node::Literal(text) if text.chars().count() == 1 => vec![format!("the character {:?}", text)],
+        Node::Literal(text) => vec![format!("the text {:?}", text)],
+        Node::Raw(source) => vec![format!("the raw pattern `{}`", source)],
+        Node::Class(class) => vec![describe_class(class, flags)],
+        Node::Anchor(anchor) => vec![describe_anchor(*anchor).to_string()],
+        Node::Concat(nodes) => list("a sequence of:", nodes, flags, groups),
+        Node::Alt(nodes) if nodes.is_empty() => vec!["nothing, this never matches".to_string()],
+        Node::Alt(nodes) => list("one of:", nodes, flags, groups),
+        Node::Repeat { node, min, max, greedy } => {
+            let mut header = match (min, max) {
+                (0, Some(1)) => "optionally".to_string(),
+                (0, None) => "zero or more".to_string(),
+                (1, None) => "one or more".to_string(),
+                (min, None) => format!("at least {}", min),
+                (min, Some(max)) if min == max => format!("exactly {}", min),
+                (min, Some(max)) => format!("between {} and {}", min, max),
+            };
+            if !greedy {
+                header.push_str(", as few as possible,");
+            }
+            if (*min, *max) != (0, Some(1)) {
+                header.push_str(" of");
+            }
+            header.push(':');
+            nest(header, describe(node, flags, groups))
+        }
+        Node::Group { node, capturing: false, name: None } => describe(node, flags, groups),
+        Node::Group { node, name, .. } => {
+            *groups += 1;
+            let header = match name {
+                Some(name) => format!("capture group {} {:?}:", groups, name),
+                None => format!("capture group {}:", groups),
+            };
+            nest(header, describe(node, flags, groups))
+        }
+        Node::Look { node, ahead, negated } => {
+            let header = match (ahead, negated) {
+                (true, false) => "followed by:",
+                (true, true) => "not followed by:",
+                (false, false) => "preceded by:",
+                (false, true) => "not preceded by:",
+            };
+            nest(header.to_string(), describe(node, flags, groups))
+        }
+        Node::Flags { node, flags: inner } => {
+            nest(format!("{}:", describe_flags(inner)), describe(node, flags.then(*inner), groups))
+        }
+        Node::Backref(group) => vec![format!("the text matched by {}", describe_group(group))],
+    }
+}
+
+fn list(header: &str, nodes: &[Node], flags: Flags, groups: &mut u32) -> Vec<String> {
+    let mut lines = vec![header.to_string()];
+    for node in nodes {
+        lines.extend(describe(node, flags, groups).into_iter().map(|line| format!("  {}", line)));
+    }
+    lines
+}
+
+// A single-line body stays on the header's line.
+fn nest(header: String, body: Vec<String>) -> Vec<String> {
+    match body.as_slice() {
+        [line] => vec![format!("{} {}", header, line)],
+        _ => {
+            let mut lines = vec![header];
+            lines.extend(body.into_iter().map(|line| format!("  {}", line)));
+            lines
+        }
+    }
+}
+
+pub(crate) fn describe_group(group: &GroupRef) -> String {
+    match group {
+        GroupRef::Index(index) => format!("capture group {}", index),
+        GroupRef::Name(name) => format!("capture group {:?}", name),
+    }
+}
+
+pub(crate) fn describe_anchor(anchor: Anchor) -> &'static str {
+    match anchor {
+        Anchor::Start => "the start of the text",
+        Anchor::End => "the end of the text",
+        Anchor::WordBoundary => "a word boundary",
+        Anchor::NotWordBoundary => "not a word boundary",
+    }
+}
+
+// `flags` decide whether `Class::Any` includes the newline.
+pub(crate) fn describe_class(class: &Class, flags: Flags) -> String {
+    match class {
+        Class::Any if flags.dot_matches_new_line == Some(true) => "any character".to_string(),
+        Class::Any => "any character except a newline".to_string(),
+        Class::Perl { class, negated } => describe_perl(*class, *negated).to_string(),
+        Class::Unicode { name, negated: false } => format!("a character in Unicode class {}", name),
+        Class::Unicode { name, negated: true } => format!("a character not in Unicode class {}", name),
+        Class::Set { items, negated: false } => format!("one of {}", describe_items(items)),
+        Class::Set { items, negated: true } => format!("any character except {}", describe_items(items)),
+        Class::Intersection(left, right) => {
+            format!("a character that is both ({}) and ({})", describe_nested(left), describe_nested(right))
+        }
+        Class::Difference(left, right) => {
+            format!("a character that is ({}) but not ({})", describe_nested(left), describe_nested(right))
+        }
+    }
+}
+
+fn describe_perl(class: PerlClass, negated: bool) -> &'static str {
+    match (class, negated) {
+        (PerlClass::Digit, false) => "a digit",
+        (PerlClass::Digit, true) => "a non-digit",
+        (PerlClass::Word, false) => "a word character",
+        (PerlClass::Word, true) => "a non-word character",
+        (PerlClass::Space, false) => "whitespace",
+        (PerlClass::Space, true) => "non-whitespace",
+    }
+}
+
+// Inside other classes `Class::Any` includes the newline.
+fn describe_nested(class: &Class) -> String {
+    match class {
+        Class::Any => "any character".to_string(),
+        class => describe_class(class, Flags::default()),
+    }
+}
+
+fn describe_items(items: &[ClassItem]) -> String {
+    let items: Vec<_> = items
+        .iter()
+        .map(|item| match item {
+            ClassItem::Char(c) => format!("{:?}", c),
+            ClassItem::Range(from, to) => format!("{:?} to {:?}", from, to),
+            ClassItem::Perl { class, negated } => describe_perl(*class, *negated).to_string(),
+            ClassItem::Unicode { name, negated } => {
+                describe_class(&Class::Unicode { name: name.clone(), negated: *negated }, Flags::default())
+            }
+            ClassItem::Class(class) => format!("({})", describe_nested(class)),
+        })
+        .collect();
+    match items.as_slice() {
+        [] => "nothing".to_string(),
+        [item] => item.clone(),
+        [rest @ .., last] => format!("{} or {}", rest.join(", "), last),
+    }
+}
+
+pub(crate) fn describe_flags(flags: &Flags) -> String {
+    let names = [
+        (flags.case_insensitive, "case-insensitive", "case-sensitive"),
+        (flags.multi_line, "multi-line", "single-line"),
+        (flags.dot_matches_new_line, "dot matching newlines", "dot not matching newlines"),
+        (flags.ignore_whitespace, "ignoring whitespace", "not ignoring whitespace"),
+        (flags.swap_greed, "lazy by default", "greedy by default"),
+        (flags.unicode, "Unicode-aware", "ASCII-only"),
+    ];
+    let names: Vec<_> = names
+        .iter()
+        .filter_map(|(flag, on, off)| flag.map(|yes| if yes { *on } else { *off }))
+        .collect();
+    match names.as_slice() {
+        [] => "as a group".to_string(),
+        _ => names.join(", "),
+    }
+}
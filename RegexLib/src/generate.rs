@@ -0,0 +1,344 @@
+use rand::{Rng, RngExt};
+use regex_syntax::hir::{Class as HirClass, HirKind};
+use regex_syntax::ParserBuilder;
+
+use crate::ast::{Class, Flags, GroupRef, Node};
+use crate::compiled::{BuildError, CompiledRegex};
+use crate::RegexPart;
+
+/// Bounds for `RegexPart::generate`.
+///
+/// ```
+/// use RegexLib::GenerateLimits;
+///
+/// let limits = GenerateLimits::new().repeat_cap(3).attempts(20);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GenerateLimits {
+    repeat_cap: u32,
+    attempts: u32,
+}
+
+impl Default for GenerateLimits {
+    fn default() -> Self {
+        GenerateLimits { repeat_cap: 8, attempts: 100 }
+    }
+}
+
+impl GenerateLimits {
+    pub fn new() -> Self {
+        GenerateLimits::default()
+    }
+
+    /// Repetitions run at most `cap` times beyond their minimum, which
+    /// keeps `*`, `+` and `{n,}` finite. Explicit maximums are respected
+    /// as well. Defaults to 8.
+    pub fn repeat_cap(mut self, cap: u32) -> Self {
+        self.repeat_cap = cap;
+        self
+    }
+
+    /// Number of candidates tried before giving up. Defaults to 100.
+    pub fn attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts;
+        self
+    }
+}
+
+impl RegexPart {
+    /// Produces a random string that the pattern matches as a whole.
+    ///
+    /// Characters are mostly drawn from printable ASCII where a class
+    /// allows it, so examples stay readable. Anchors, word boundaries and
+    /// lookaround are not planned for; candidates that violate them are
+    /// discarded. Returns `None` if the pattern does not compile or no
+    /// candidate within `limits` matches, e.g. for `sequence!(escape!("a"), begin!())`.
+    ///
+    /// ```
+    /// use rand::SeedableRng;
+    /// use RegexLib::*;
+    ///
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    /// let version = sequence!(one_or_more!(digit!()), escape!("."), between!(digit!(), 1, 2));
+    /// let example = version.generate(&mut rng, &GenerateLimits::new()).unwrap();
+    /// assert!(version.compile().unwrap().is_match(&example));
+    /// ```
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R, limits: &GenerateLimits) -> Option<String> {
+        let whole = self.whole_match().ok()?;
+        let mut names = Vec::new();
+        self.node().for_each(&mut |node| match node {
+            Node::Group { capturing: false, name: None, .. } => {}
+            Node::Group { name, .. } => names.push(name.clone()),
+            _ => {}
+        });
+        let mut generator = Generator { rng, limits, names, captured: Vec::new() };
+        for _ in 0..limits.attempts {
+            generator.captured = vec![None; generator.names.len()];
+            let mut out = String::new();
+            if generator.node(self.node(), Flags::default(), Some(0), &mut out) && whole.is_match(&out) {
+                return Some(out);
+            }
+        }
+        None
+    }
+
+    /// Shrinks `example` to a shorter and simpler string that still
+    /// matches the pattern as a whole and for which `keep` holds, such as
+    /// the smallest input a parser still fails on. Characters are removed
+    /// in chunks, then replaced by `a`, `0` or a space where possible.
+    ///
+    /// `example` is returned unchanged if it does not match or fails `keep`.
+    ///
+    /// ```
+    /// use RegexLib::*;
+    ///
+    /// let assignment = sequence!(one_or_more!(alpha_numeric!()), escape!("="), one_or_more!(digit!()));
+    /// assert_eq!(assignment.shrink("total=1789", |text| text.contains('7')), "a=7");
+    /// ```
+    pub fn shrink<F: FnMut(&str) -> bool>(&self, example: &str, mut keep: F) -> String {
+        let Ok(whole) = self.whole_match() else {
+            return example.to_string();
+        };
+        let mut accept = |candidate: &[char]| {
+            let candidate: String = candidate.iter().collect();
+            whole.is_match(&candidate) && keep(&candidate)
+        };
+        let mut chars: Vec<char> = example.chars().collect();
+        if !accept(&chars) {
+            return example.to_string();
+        }
+        let mut progress = true;
+        while progress {
+            progress = false;
+            let mut size = chars.len();
+            while size > 0 {
+                let mut start = 0;
+                while start + size <= chars.len() {
+                    let candidate: Vec<char> = chars[..start].iter().chain(&chars[start + size..]).copied().collect();
+                    if accept(&candidate) {
+                        chars = candidate;
+                        progress = true;
+                    } else {
+                        start += 1;
+                    }
+                }
+                size /= 2;
+            }
+            for i in 0..chars.len() {
+                for simpler in SIMPLE.iter().take_while(|&&simpler| simplicity(simpler) < simplicity(chars[i])) {
+                    let mut candidate = chars.clone();
+                    candidate[i] = *simpler;
+                    if accept(&candidate) {
+                        chars = candidate;
+                        progress = true;
+                        break;
+                    }
+                }
+            }
+        }
+        chars.into_iter().collect()
+    }
+
+    fn whole_match(&self) -> Result<CompiledRegex, BuildError> {
+        RegexPart::sequence([RegexPart::new(r"\A"), self.clone(), RegexPart::new(r"\z")]).compile()
+    }
+}
+
+// Replacements tried by `shrink`, simplest first.
+const SIMPLE: [char; 3] = ['a', '0', ' '];
+
+fn simplicity(c: char) -> u32 {
+    match SIMPLE.iter().position(|&simple| simple == c) {
+        Some(index) => index as u32,
+        None => SIMPLE.len() as u32 + c as u32,
+    }
+}
+
+struct Generator<'a, R: ?Sized> {
+    rng: &'a mut R,
+    limits: &'a GenerateLimits,
+    // Names of the capture groups in the order they are numbered, and the
+    // text generated for each in the current attempt.
+    names: Vec<Option<String>>,
+    captured: Vec<Option<String>>,
+}
+
+impl<R: Rng + ?Sized> Generator<'_, R> {
+    // Appends a candidate for `node` to `out`, or returns false if the
+    // node can not match anything, like an empty class. `group` is the
+    // index of the first capture group within `node`, or `None` for groups
+    // inside `raw!` text, which are not tracked.
+    fn node(&mut self, node: &Node, flags: Flags, group: Option<usize>, out: &mut String) -> bool {
+        match node {
+            Node::Empty | Node::Anchor(_) | Node::Look { .. } => true,
+            Node::Literal(text) => {
+                for c in text.chars() {
+                    if flags.case_insensitive == Some(true) && self.rng.random_bool(0.5) {
+                        out.push(swap_case(c));
+                    } else {
+                        out.push(c);
+                    }
+                }
+                true
+            }
+            Node::Raw(source) => match RegexPart::parse(source) {
+                Ok(part) => self.node(part.node(), flags, None, out),
+                Err(_) => false,
+            },
+            Node::Class(class) => match class_ranges(class, flags).and_then(|ranges| self.pick(&ranges)) {
+                Some(c) => {
+                    out.push(c);
+                    true
+                }
+                None => false,
+            },
+            Node::Concat(nodes) => {
+                let mut group = group;
+                nodes.iter().all(|node| {
+                    let matched = self.node(node, flags, group, out);
+                    group = group.map(|group| group + group_count(node));
+                    matched
+                })
+            }
+            Node::Alt(nodes) => {
+                // Start at a random branch and fall through to the others
+                // if it can not match.
+                let len = out.len();
+                let first = if nodes.is_empty() { 0 } else { self.rng.random_range(0..nodes.len()) };
+                for index in (first..nodes.len()).chain(0..first) {
+                    let preceding: usize = nodes[..index].iter().map(group_count).sum();
+                    if self.node(&nodes[index], flags, group.map(|group| group + preceding), out) {
+                        return true;
+                    }
+                    out.truncate(len);
+                }
+                false
+            }
+            Node::Repeat { node, min, max, .. } => {
+                let upper = max.unwrap_or(u32::MAX).min(min.saturating_add(self.limits.repeat_cap));
+                let count = self.rng.random_range(*min..=upper);
+                (0..count).all(|_| self.node(node, flags, group, out))
+            }
+            Node::Group { node: inner, capturing: false, name: None } => self.node(inner, flags, group, out),
+            Node::Group { node: inner, .. } => {
+                let start = out.len();
+                if !self.node(inner, flags, group.map(|group| group + 1), out) {
+                    return false;
+                }
+                if let Some(group) = group {
+                    self.captured[group] = Some(out[start..].to_string());
+                }
+                true
+            }
+            Node::Flags { node, flags: inner } => self.node(node, flags.then(*inner), group, out),
+            Node::Backref(group) => {
+                let index = match group {
+                    GroupRef::Index(index) => (*index as usize).checked_sub(1),
+                    GroupRef::Name(name) => self.names.iter().position(|group| group.as_ref() == Some(name)),
+                };
+                // A group that has not matched makes the reference fail.
+                match index.and_then(|index| self.captured.get(index).cloned().flatten()) {
+                    Some(text) => {
+                        out.push_str(&text);
+                        true
+                    }
+                    None => false,
+                }
+            }
+        }
+    }
+
+    // Mostly printable ASCII, which reads well in examples, while every
+    // character of the class stays possible.
+    fn pick(&mut self, ranges: &[(char, char)]) -> Option<char> {
+        let printable: Vec<_> = ranges
+            .iter()
+            .map(|&(from, to)| (from.max(' '), to.min('~')))
+            .filter(|(from, to)| from <= to)
+            .collect();
+        let pool = if !printable.is_empty() && self.rng.random_bool(0.75) { &printable[..] } else { ranges };
+        let total: u32 = pool.iter().map(|&(from, to)| range_len(from, to)).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut index = self.rng.random_range(0..total);
+        for &(from, to) in pool {
+            let size = range_len(from, to);
+            if index < size {
+                let mut code = from as u32 + index;
+                // Ranges may span the surrogate gap, which is not counted.
+                if (from as u32) < SURROGATES.0 && code >= SURROGATES.0 {
+                    code += SURROGATES.1 - SURROGATES.0 + 1;
+                }
+                return char::from_u32(code);
+            }
+            index -= size;
+        }
+        unreachable!()
+    }
+}
+
+// First and last surrogate code point, which are not characters.
+const SURROGATES: (u32, u32) = (0xD800, 0xDFFF);
+
+// Number of characters in `from..=to`.
+fn range_len(from: char, to: char) -> u32 {
+    let len = to as u32 - from as u32 + 1;
+    if (from as u32) < SURROGATES.0 && to as u32 > SURROGATES.1 {
+        len - (SURROGATES.1 - SURROGATES.0 + 1)
+    } else {
+        len
+    }
+}
+
+// Number of capture groups in `node`, not counting those inside `raw!` text.
+fn group_count(node: &Node) -> usize {
+    let mut count = 0;
+    node.for_each(&mut |node| match node {
+        Node::Group { capturing: false, name: None, .. } => {}
+        Node::Group { .. } => count += 1,
+        _ => {}
+    });
+    count
+}
+
+// Resolves the class with `regex-syntax`, which knows the Unicode tables
+// and case folding.
+fn class_ranges(class: &Class, flags: Flags) -> Option<Vec<(char, char)>> {
+    let mut source = String::new();
+    class.render(&mut source);
+    let hir = ParserBuilder::new()
+        .case_insensitive(flags.case_insensitive == Some(true))
+        .dot_matches_new_line(flags.dot_matches_new_line == Some(true))
+        .unicode(flags.unicode != Some(false))
+        .build()
+        .parse(&source)
+        .ok()?;
+    match hir.kind() {
+        HirKind::Class(HirClass::Unicode(class)) => {
+            Some(class.ranges().iter().map(|range| (range.start(), range.end())).collect())
+        }
+        HirKind::Class(HirClass::Bytes(class)) => Some(
+            class
+                .ranges()
+                .iter()
+                .filter(|range| range.start().is_ascii())
+                .map(|range| (range.start() as char, range.end().min(0x7F) as char))
+                .collect(),
+        ),
+        HirKind::Literal(literal) => {
+            let c = std::str::from_utf8(&literal.0).ok()?.chars().next()?;
+            Some(vec![(c, c)])
+        }
+        _ => None,
+    }
+}
+
+fn swap_case(c: char) -> char {
+    let swapped: String = if c.is_lowercase() { c.to_uppercase().collect() } else { c.to_lowercase().collect() };
+    let mut chars = swapped.chars();
+    match (chars.next(), chars.next()) {
+        (Some(single), None) => single,
+        _ => c,
+    }
+}
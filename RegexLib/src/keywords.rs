@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use crate::ast::{Anchor, Class, ClassItem, Flags, Node};
+use crate::RegexPart;
+
+/// Starts a pattern matching any of `words`; see `Keywords`.
+///
+/// ```
+/// use RegexLib::keywords;
+///
+/// let part = keywords(["for", "foreach", "fn", "if"]).whole_words(true).build();
+/// assert_eq!(part.source(), r"\b(?:f(?:or(?:each)?|n)|if)\b");
+///
+/// let regex = part.compile().unwrap();
+/// let found: Vec<_> = regex.find_iter("foreach fn format").map(|m| m.as_str()).collect();
+/// assert_eq!(found, ["foreach", "fn"]);
+/// ```
+pub fn keywords<I>(words: I) -> Keywords
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    Keywords {
+        words: words.into_iter().map(|word| word.as_ref().to_string()).collect(),
+        whole_words: false,
+        case_insensitive: false,
+    }
+}
+
+/// Builder for an alternation of literal words.
+///
+/// The words are stored in a prefix trie, so shared prefixes are spelled
+/// out once and final characters are folded into classes. Where one word
+/// is a prefix of another, the longer one is tried first. Empty words are
+/// ignored and an empty set matches nothing.
+#[derive(Debug, Clone)]
+pub struct Keywords {
+    words: Vec<String>,
+    whole_words: bool,
+    case_insensitive: bool,
+}
+
+impl Keywords {
+    /// Surrounds the alternation with `\b`, so that only whole words
+    /// match. This assumes the words start and end with word characters.
+    pub fn whole_words(mut self, yes: bool) -> Self {
+        self.whole_words = yes;
+        self
+    }
+
+    /// Matches the words regardless of case, within a `(?i:...)` group.
+    pub fn case_insensitive(mut self, yes: bool) -> Self {
+        self.case_insensitive = yes;
+        self
+    }
+
+    pub fn build(&self) -> RegexPart {
+        let mut trie = Trie::default();
+        for word in &self.words {
+            if self.case_insensitive {
+                trie.insert(&word.chars().map(simple_lowercase).collect::<String>());
+            } else {
+                trie.insert(word);
+            }
+        }
+        let mut node = trie.alternatives();
+        if self.case_insensitive {
+            node = Node::Flags { node: Box::new(node), flags: Flags { case_insensitive: Some(true), ..Flags::default() } };
+        }
+        if self.whole_words {
+            let boundary = Node::Anchor(Anchor::WordBoundary);
+            node = Node::Concat(vec![boundary.clone(), node, boundary]);
+        }
+        RegexPart::from(node).optimize()
+    }
+}
+
+impl From<Keywords> for RegexPart {
+    fn from(keywords: Keywords) -> Self {
+        keywords.build()
+    }
+}
+
+// Folds words that differ only in case onto one spelling. Characters
+// whose lowercase form is several characters, like `İ`, are kept: `(?i)`
+// only folds single characters, so `i\u{307}` would not match `İ`.
+fn simple_lowercase(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(single), None) => single,
+        _ => c,
+    }
+}
+
+#[derive(Debug, Default)]
+struct Trie {
+    terminal: bool,
+    children: BTreeMap<char, Trie>,
+}
+
+impl Trie {
+    fn insert(&mut self, word: &str) {
+        let mut node = self;
+        for c in word.chars() {
+            node = node.children.entry(c).or_default();
+        }
+        // The root stands for the empty word, which is dropped.
+        if !word.is_empty() {
+            node.terminal = true;
+        }
+    }
+
+    // Every non-empty continuation of this node. Children start with
+    // distinct characters, so at most one of the branches can match.
+    fn alternatives(&self) -> Node {
+        let mut branches = Vec::new();
+        let mut leaves = Vec::new();
+        for (&c, child) in &self.children {
+            if child.children.is_empty() {
+                leaves.push(ClassItem::Char(c));
+                continue;
+            }
+            let mut rest = child.alternatives();
+            if child.terminal {
+                // Greedy, so the longer word wins.
+                rest = Node::Repeat { node: Box::new(rest), min: 0, max: Some(1), greedy: true };
+            }
+            branches.push(Node::Concat(vec![Node::Literal(c.to_string()), rest]));
+        }
+        if !leaves.is_empty() {
+            branches.push(Node::Class(Class::Set { items: leaves, negated: false }));
+        }
+        Node::Alt(branches)
+    }
+}
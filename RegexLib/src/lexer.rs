@@ -0,0 +1,205 @@
+use std::ops::Range;
+
+use regex::RegexSet;
+
+use crate::compiled::{BuildError, CompiledRegex};
+use crate::RegexPart;
+
+/// Splits text into tokens by a list of rules, as generated by `lexer!`.
+///
+/// At every position each rule is tried against the rest of the input.
+/// Among the rules, the longest match wins and ties go to the rule listed
+/// first, so keywords are listed before identifiers. Within a single rule
+/// the match is the one the regex engine finds, which for an alternation
+/// is the first branch that matches, not the longest: a rule
+/// `any_of!(escape!("a"), escape!("ab"))` on "ab" gives a token "a". List
+/// longer branches first, or split them into separate rules. Rules without
+/// a token are skipped, and runs of input no rule matches come out as the
+/// `error` token. Matches of zero length do not count.
+///
+/// Rules see the input from their own start on, so word boundaries and
+/// lookbehind at the start of a rule do not look at the previous token. A
+/// rule the backtracking engine gives up on does not match there, as with
+/// `CompiledRegex::is_match`.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// let lexer = Lexer::new(
+///     [(Some("number"), one_or_more!(digit!())), (None, one_or_more!(whitespace!()))],
+///     "error",
+/// )
+/// .unwrap();
+/// let tokens: Vec<_> = lexer.tokens("12 x 3").map(|(token, _, text)| (token, text)).collect();
+/// assert_eq!(tokens, [("number", "12"), ("error", "x"), ("number", "3")]);
+/// ```
+#[derive(Debug)]
+pub struct Lexer<T> {
+    rules: Vec<(Option<T>, CompiledRegex)>,
+    // Tells which rules match at a position in one pass. Only built when
+    // no rule needs the backtracking engine.
+    set: Option<RegexSet>,
+    error: T,
+}
+
+impl<T> Lexer<T> {
+    pub fn new<I>(rules: I, error: T) -> Result<Self, BuildError>
+    where
+        I: IntoIterator<Item = (Option<T>, RegexPart)>,
+    {
+        let mut compiled = Vec::new();
+        let mut sources = Vec::new();
+        let mut fancy = false;
+        for (token, part) in rules {
+            let anchored = RegexPart::sequence([RegexPart::new(r"\A"), part]);
+            fancy |= anchored.node().fancy_construct().is_some();
+            compiled.push((token, anchored.compile()?));
+            sources.push(anchored.source());
+        }
+        let set = if fancy { None } else { Some(RegexSet::new(sources)?) };
+        Ok(Lexer { rules: compiled, set, error })
+    }
+
+    pub fn tokens<'l, 't>(&'l self, text: &'t str) -> Tokens<'l, 't, T> {
+        Tokens { lexer: self, text, position: 0 }
+    }
+
+    // The rule with the longest match at the start of `rest`, and the
+    // length of that match. Each rule contributes its leftmost-first match.
+    fn longest(&self, rest: &str) -> Option<(&Option<T>, usize)> {
+        let candidates: Box<dyn Iterator<Item = usize>> = match &self.set {
+            Some(set) => Box::new(set.matches(rest).into_iter()),
+            None => Box::new(0..self.rules.len()),
+        };
+        let mut best: Option<(&Option<T>, usize)> = None;
+        for index in candidates {
+            let (token, regex) = &self.rules[index];
+            let len = regex.find(rest).map_or(0, |found| found.len());
+            if len > best.map_or(0, |(_, best)| best) {
+                best = Some((token, len));
+            }
+        }
+        best
+    }
+}
+
+/// Iterator over the tokens of a text, with their byte ranges and text.
+///
+/// Returned by `Lexer::tokens` and the `lex` function of `lexer!` types.
+#[derive(Debug)]
+pub struct Tokens<'l, 't, T> {
+    lexer: &'l Lexer<T>,
+    text: &'t str,
+    position: usize,
+}
+
+impl<'l, 't, T: Clone> Iterator for Tokens<'l, 't, T> {
+    type Item = (T, Range<usize>, &'t str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut start = self.position;
+        while self.position < self.text.len() {
+            let rest = &self.text[self.position..];
+            match self.lexer.longest(rest) {
+                None => self.position += rest.chars().next().map_or(1, char::len_utf8),
+                // Finish the unmatched run first; the match is found
+                // again on the next call.
+                Some(_) if start < self.position => break,
+                Some((token, len)) => {
+                    let range = self.position..self.position + len;
+                    self.position = range.end;
+                    match token {
+                        Some(token) => return Some((token.clone(), range.clone(), &self.text[range])),
+                        None => start = self.position,
+                    }
+                }
+            }
+        }
+        if start < self.position {
+            let range = start..self.position;
+            Some((self.lexer.error.clone(), range.clone(), &self.text[range]))
+        } else {
+            None
+        }
+    }
+}
+
+/// Declares a token enum with a lexer built from combinator rules.
+///
+/// Each variant is followed by the pattern for its tokens; `skip` drops
+/// what the pattern matches, such as whitespace, without a variant. An
+/// `Error` variant is added for input no rule matches. `lex` returns an
+/// iterator of `(token, byte range, text)`; the enum must be `Clone`.
+/// See `Lexer` for how rules are chosen.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// lexer! {
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     pub enum Token {
+///         Let => sequence!(escape!("let"), word_boundary!()),
+///         Ident => sequence!(char_class!['a'..='z', '_'], zero_or_more!(alpha_numeric!())),
+///         Number => one_or_more!(digit!()),
+///         Equals => escape!("="),
+///         Ws => skip one_or_more!(whitespace!()),
+///     }
+/// }
+///
+/// let tokens: Vec<_> = Token::lex("let letter = 42 ?").map(|(token, _, text)| (token, text)).collect();
+/// assert_eq!(
+///     tokens,
+///     [
+///         (Token::Let, "let"),
+///         (Token::Ident, "letter"),
+///         (Token::Equals, "="),
+///         (Token::Number, "42"),
+///         (Token::Error, "?")
+///     ]
+/// );
+/// ```
+#[macro_export]
+macro_rules! lexer {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident {
+            $($rules:tt)*
+        }
+    ) => {
+        $crate::lexer!(@rules [$(#[$attr])* $vis enum] $name [] [] $($rules)*);
+    };
+    (@rules $head:tt $name:ident [$($variants:tt)*] [$($rules:tt)*]
+        $variant:ident => skip $pattern:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::lexer!(@rules $head $name [$($variants)*] [$($rules)* (::core::option::Option::None, $pattern)]
+            $($($rest)*)?);
+    };
+    (@rules $head:tt $name:ident [$($variants:tt)*] [$($rules:tt)*]
+        $(#[$variant_attr:meta])* $variant:ident => $pattern:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::lexer!(@rules $head $name [$($variants)* $(#[$variant_attr])* $variant,]
+            [$($rules)* (::core::option::Option::Some($name::$variant), $pattern)] $($($rest)*)?);
+    };
+    (@rules [$($head:tt)*] $name:ident [$($variants:tt)*] [$($rules:tt)*]) => {
+        $($head)* $name {
+            $($variants)*
+            /// Input that no rule matches.
+            Error,
+        }
+
+        impl $name {
+            /// The rules compiled on first use.
+            pub fn lexer() -> &'static $crate::Lexer<$name> {
+                $crate::lazy_static::lazy_static! {
+                    static ref LEXER: $crate::Lexer<$name> = $crate::Lexer::new([$($rules),*], $name::Error)
+                        .unwrap_or_else(|error| panic!("lexer! `{}`: {}", stringify!($name), error));
+                }
+                &*LEXER
+            }
+
+            pub fn lex(text: &str) -> $crate::Tokens<'static, '_, $name> {
+                Self::lexer().tokens(text)
+            }
+        }
+    };
+}
@@ -0,0 +1,763 @@
+#![allow(non_snake_case)]
+
+// Lets `regex_const!` expand to `::RegexLib::...` paths inside this crate too.
+extern crate self as RegexLib;
+
+use std::fmt;
+use std::ops::{Add, BitOr, Bound, RangeBounds};
+
+pub use RegexAst::ast;
+mod class;
+mod compiled;
+mod explain;
+mod generate;
+mod keywords;
+mod lexer;
+mod optimize;
+mod parse;
+mod railroad;
+mod rust_code;
+mod set;
+#[doc(hidden)]
+pub use RegexAst::{combinators, escape};
+pub mod patterns;
+mod typed;
+
+pub use ast::{Flags, GroupRef, Node, MARKS};
+pub use class::IntoClass;
+pub use compiled::{BuildError, Captures, CompileOptions, CompiledRegex, ConstRegex, Match, Matches, SearchError, Split};
+pub use escape::escape;
+pub use generate::GenerateLimits;
+pub use keywords::{keywords, Keywords};
+pub use lexer::{Lexer, Tokens};
+pub use parse::ParseError;
+pub use set::RegexPartSet;
+pub use typed::MatchError;
+#[doc(hidden)]
+pub use typed::{Field, OptionalField, RequiredField};
+
+/// Evaluates a combinator expression at compile time.
+///
+/// The pattern is rendered exactly like `RegexPart::source` and validated
+/// with `regex-syntax`; mistakes are reported as compile errors on the
+/// offending combinator. Expands to a `&'static ConstRegex`.
+///
+/// ```
+/// use RegexLib::regex_const;
+///
+/// let number = regex_const! { sequence!(one_or_more!(digit!()), optional!(escape!("."))) };
+/// assert_eq!(number.as_str(), r"\d+\.?");
+/// assert!(number.is_match("42."));
+/// ```
+pub use RegexConst::regex_const;
+
+#[doc(hidden)]
+pub use lazy_static;
+
+/// A regex fragment, kept as a syntax tree until it is rendered or compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexPart {
+    node: Node,
+}
+
+impl RegexPart {
+    /// Wraps pattern text verbatim; see `raw!`.
+    pub fn new(source: &str) -> Self {
+        combinators::raw(source).into()
+    }
+
+    pub fn literal(text: &str) -> Self {
+        combinators::escape(text).into()
+    }
+
+    pub fn sequence<I: IntoIterator<Item = RegexPart>>(parts: I) -> Self {
+        combinators::sequence(parts.into_iter().map(RegexPart::into_node).collect()).into()
+    }
+
+    pub fn any_of<I: IntoIterator<Item = RegexPart>>(parts: I) -> Self {
+        combinators::any_of(parts.into_iter().map(RegexPart::into_node).collect()).into()
+    }
+
+    /// # Panics
+    ///
+    /// If `max` is less than `min`.
+    pub fn repeated(self, min: u32, max: Option<u32>, greedy: bool) -> Self {
+        combinators::repeat(self.node, min, max, greedy).unwrap_or_else(|message| panic!("{}", message)).into()
+    }
+
+    pub fn group(self, capturing: bool, name: Option<&str>) -> Self {
+        combinators::group(self.node, capturing, name).into()
+    }
+
+    pub fn look(self, ahead: bool, negated: bool) -> Self {
+        combinators::look(self.node, ahead, negated).into()
+    }
+
+    /// `self*`.
+    pub fn many(self) -> Self {
+        self.apply(combinators::zero_or_more)
+    }
+
+    /// `self+`.
+    pub fn many1(self) -> Self {
+        self.apply(combinators::one_or_more)
+    }
+
+    /// `self?`.
+    pub fn opt(self) -> Self {
+        self.apply(combinators::optional)
+    }
+
+    /// Repeats `self` as often as `counts` allows: `2..=4` is `{2,4}`,
+    /// `3..` is `{3,}` and `..5` is `{0,4}`.
+    ///
+    /// # Panics
+    ///
+    /// If the range is empty, including ranges that start after `u32::MAX`.
+    pub fn repeat<R: RangeBounds<u32>>(self, counts: R) -> Self {
+        let min = match counts.start_bound() {
+            Bound::Included(&min) => min,
+            Bound::Excluded(&min) => min.checked_add(1).expect("empty repetition range"),
+            Bound::Unbounded => 0,
+        };
+        let max = match counts.end_bound() {
+            Bound::Included(&max) => Some(max),
+            Bound::Excluded(&max) => Some(max.checked_sub(1).expect("empty repetition range")),
+            Bound::Unbounded => None,
+        };
+        self.repeated(min, max, true)
+    }
+
+    pub fn capture(self) -> Self {
+        self.apply(combinators::capturing_group)
+    }
+
+    pub fn named(self, name: &str) -> Self {
+        combinators::named_group(name, self.node).into()
+    }
+
+    /// Makes the repetition `self` match as few times as possible. Parts
+    /// that are not repetitions are returned unchanged.
+    pub fn lazy(self) -> Self {
+        match self.node {
+            Node::Repeat { node, min, max, .. } => Node::Repeat { node, min, max, greedy: false }.into(),
+            node => node.into(),
+        }
+    }
+
+    /// Applies inline flags to this part only, as in `(?i:...)`.
+    pub fn with_flags(self, flags: Flags) -> Self {
+        combinators::with_flags(self.node, flags).into()
+    }
+
+    /// Wraps the tree with one of the `combinators`; used by the macros.
+    #[doc(hidden)]
+    pub fn apply(self, combinator: fn(Node) -> Node) -> Self {
+        combinator(self.node).into()
+    }
+
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    pub fn into_node(self) -> Node {
+        self.node
+    }
+
+    /// Renders the pattern text.
+    pub fn source(&self) -> String {
+        let mut source = String::new();
+        self.node.render(&mut source);
+        source
+    }
+}
+
+impl From<Node> for RegexPart {
+    fn from(node: Node) -> Self {
+        RegexPart { node }
+    }
+}
+
+impl fmt::Display for RegexPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.node, f)
+    }
+}
+
+impl From<&RegexPart> for RegexPart {
+    fn from(part: &RegexPart) -> Self {
+        part.clone()
+    }
+}
+
+// Joins two parts into one `Concat` or `Alt`, flattening nested ones.
+fn join(left: RegexPart, right: RegexPart, alternation: bool) -> RegexPart {
+    let mut nodes = Vec::new();
+    for node in [left.node, right.node] {
+        match node {
+            Node::Alt(nested) if alternation => nodes.extend(nested),
+            Node::Concat(nested) if !alternation => nodes.extend(nested),
+            node => nodes.push(node),
+        }
+    }
+    if alternation {
+        Node::Alt(nodes).into()
+    } else {
+        Node::Concat(nodes).into()
+    }
+}
+
+/// `a + b` matches `a` followed by `b`, like `sequence!(a, b)`.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// let key = alpha_numeric!().many1().named("key");
+/// let pair = key + escape!("=") + (digit!().many1() | escape!("null"));
+/// assert_eq!(pair.source(), r"(?P<key>\w+)=(?:\d+|null)");
+/// ```
+impl<R: Into<RegexPart>> Add<R> for RegexPart {
+    type Output = RegexPart;
+
+    fn add(self, other: R) -> RegexPart {
+        join(self, other.into(), false)
+    }
+}
+
+impl<R: Into<RegexPart>> Add<R> for &RegexPart {
+    type Output = RegexPart;
+
+    fn add(self, other: R) -> RegexPart {
+        join(self.clone(), other.into(), false)
+    }
+}
+
+/// `a | b` matches `a` or else `b`, like `any_of!(a, b)`.
+impl<R: Into<RegexPart>> BitOr<R> for RegexPart {
+    type Output = RegexPart;
+
+    fn bitor(self, other: R) -> RegexPart {
+        join(self, other.into(), true)
+    }
+}
+
+impl<R: Into<RegexPart>> BitOr<R> for &RegexPart {
+    type Output = RegexPart;
+
+    fn bitor(self, other: R) -> RegexPart {
+        join(self.clone(), other.into(), true)
+    }
+}
+
+#[macro_export]
+macro_rules! raw {
+    ($str:expr) => {
+        $crate::RegexPart::new($str)
+    };
+}
+
+#[macro_export]
+macro_rules! escape {
+    ($str:expr) => {
+        $crate::RegexPart::literal(&$str)
+    };
+}
+
+/// Escapes a string constant at compile time, yielding a `&'static str`.
+///
+/// ```
+/// use RegexLib::escape_const;
+///
+/// const DOT: &str = escape_const!("a.b");
+/// assert_eq!(DOT, r"a\.b");
+/// ```
+#[macro_export]
+macro_rules! escape_const {
+    ($text:expr) => {{
+        const TEXT: &str = $text;
+        const LEN: usize = $crate::escape::escaped_len(TEXT);
+        const BYTES: [u8; LEN] = $crate::escape::escape_bytes::<LEN>(TEXT);
+        const ESCAPED: &str = match ::core::str::from_utf8(&BYTES) {
+            Ok(escaped) => escaped,
+            Err(_) => panic!("escape_const! produced invalid UTF-8"),
+        };
+        ESCAPED
+    }};
+}
+
+#[macro_export]
+macro_rules! non_capturing_group {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::non_capturing_group)
+    };
+}
+
+#[macro_export]
+macro_rules! capturing_group {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::capturing_group)
+    };
+}
+
+#[macro_export]
+macro_rules! named_group {
+    ($name:expr, $part:expr) => {
+        $crate::RegexPart::from($crate::combinators::named_group(&$name, $part.clone().into_node()))
+    };
+}
+
+#[macro_export]
+macro_rules! one_or_more {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::one_or_more)
+    };
+}
+
+#[macro_export]
+macro_rules! zero_or_more {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::zero_or_more)
+    };
+}
+
+#[macro_export]
+macro_rules! any_of {
+    ($($part:expr),+) => {
+        $crate::RegexPart::from($crate::combinators::any_of(vec![$($part.clone().into_node()),+]))
+    };
+}
+
+#[macro_export]
+macro_rules! sequence {
+    ($($part:expr),+) => {
+        $crate::RegexPart::from($crate::combinators::sequence(vec![$($part.clone().into_node()),+]))
+    };
+}
+
+#[macro_export]
+macro_rules! optional {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::optional)
+    };
+}
+
+#[macro_export]
+macro_rules! lazy {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::zero_or_more_lazy)
+    };
+}
+
+/// Repeats `part` at least `min` times, or between `min` and `max` times.
+#[macro_export]
+macro_rules! times {
+    ($part:expr, $min:expr) => {
+        $crate::at_least!($part, $min)
+    };
+    ($part:expr, $min:expr, $max:expr) => {
+        $crate::between!($part, $min, $max)
+    };
+}
+
+/// `part{n}`.
+#[macro_export]
+macro_rules! exactly {
+    ($part:expr, $count:expr) => {
+        $crate::RegexPart::from($crate::combinators::exactly($part.clone().into_node(), $count))
+    };
+}
+
+/// `part{n,}`.
+#[macro_export]
+macro_rules! at_least {
+    ($part:expr, $min:expr) => {
+        $crate::RegexPart::from($crate::combinators::at_least($part.clone().into_node(), $min))
+    };
+}
+
+/// `part{min,max}`; panics if `min > max`.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// assert_eq!(between!(digit!(), 2, 4).source(), r"\d{2,4}");
+/// assert_eq!(between!(digit!(), 3, 3).source(), r"\d{3}");
+/// assert_eq!(between_lazy!(digit!(), 0, 1).source(), r"\d??");
+/// ```
+#[macro_export]
+macro_rules! between {
+    ($part:expr, $min:expr, $max:expr) => {
+        $crate::RegexPart::from(
+            $crate::combinators::between($part.clone().into_node(), $min, $max)
+                .unwrap_or_else(|message| ::core::panic!("{}", message)),
+        )
+    };
+}
+
+#[macro_export]
+macro_rules! one_or_more_lazy {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::one_or_more_lazy)
+    };
+}
+
+#[macro_export]
+macro_rules! zero_or_more_lazy {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::zero_or_more_lazy)
+    };
+}
+
+#[macro_export]
+macro_rules! optional_lazy {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::optional_lazy)
+    };
+}
+
+#[macro_export]
+macro_rules! at_least_lazy {
+    ($part:expr, $min:expr) => {
+        $crate::RegexPart::from($crate::combinators::at_least_lazy($part.clone().into_node(), $min))
+    };
+}
+
+#[macro_export]
+macro_rules! between_lazy {
+    ($part:expr, $min:expr, $max:expr) => {
+        $crate::RegexPart::from(
+            $crate::combinators::between_lazy($part.clone().into_node(), $min, $max)
+                .unwrap_or_else(|message| ::core::panic!("{}", message)),
+        )
+    };
+}
+
+#[macro_export]
+macro_rules! not {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::not_followed_by)
+    };
+}
+
+#[macro_export]
+macro_rules! not_escaped {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::not_escaped)
+    };
+}
+
+/// Asserts that `part` matches next, without consuming it.
+///
+/// Lookaround needs the `fancy` feature; without it `compile` returns
+/// `BuildError::Unsupported`.
+#[macro_export]
+macro_rules! followed_by {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::followed_by)
+    };
+}
+
+/// Asserts that `part` does not match next.
+#[macro_export]
+macro_rules! not_followed_by {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::not_followed_by)
+    };
+}
+
+/// Asserts that `part` matches right before the current position.
+#[macro_export]
+macro_rules! preceded_by {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::preceded_by)
+    };
+}
+
+/// Asserts that `part` does not match right before the current position.
+#[macro_export]
+macro_rules! not_preceded_by {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::not_preceded_by)
+    };
+}
+
+/// Matches the text an earlier capture group matched, by number (`1`) or
+/// name (`"quote"`), as `\k<1>` or `\k<quote>`. Like lookaround this needs
+/// the `fancy` feature, otherwise compiling fails with
+/// `BuildError::Unsupported`.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// let quoted = sequence!(
+///     named_group!("quote", char_class!["'\""]),
+///     zero_or_more_lazy!(anything!()),
+///     backref!("quote")
+/// );
+/// assert_eq!(quoted.source(), r#"(?P<quote>['"]).*?\k<quote>"#);
+/// # #[cfg(feature = "fancy")]
+/// assert_eq!(quoted.compile().unwrap().find(r#"say "it's" twice"#).unwrap().as_str(), r#""it's""#);
+/// ```
+#[macro_export]
+macro_rules! backref {
+    ($group:expr) => {
+        $crate::RegexPart::from($crate::combinators::backref($crate::ast::GroupRef::from($group)))
+    };
+}
+
+/// Matches `part` ignoring case, as `(?i:...)`.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// let keyword = sequence!(case_insensitive!(escape!("select")), escape!(" *"));
+/// assert_eq!(keyword.source(), r"(?i:select)\ \*");
+/// let regex = keyword.compile().unwrap();
+/// assert!(regex.is_match("SeLeCt *"));
+/// assert!(!regex.is_match("select +"));
+/// ```
+#[macro_export]
+macro_rules! case_insensitive {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::case_insensitive)
+    };
+}
+
+/// Matches `part` respecting case inside a case-insensitive part, as `(?-i:...)`.
+#[macro_export]
+macro_rules! case_sensitive {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::case_sensitive)
+    };
+}
+
+/// Lets `begin!()` and `end!()` inside `part` match at line breaks, as `(?m:...)`.
+#[macro_export]
+macro_rules! multiline {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::multiline)
+    };
+}
+
+/// Lets `anything!()` inside `part` match `\n`, as `(?s:...)`.
+#[macro_export]
+macro_rules! dot_matches_newline {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::dot_matches_newline)
+    };
+}
+
+/// Ignores whitespace and `#` comments in `raw!` text inside `part`, as `(?x:...)`.
+#[macro_export]
+macro_rules! ignore_whitespace {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::ignore_whitespace)
+    };
+}
+
+/// Swaps greedy and lazy quantifiers inside `part`, as `(?U:...)`.
+#[macro_export]
+macro_rules! swap_greed {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::swap_greed)
+    };
+}
+
+/// Restricts classes such as `digit!()` inside `part` to ASCII, as `(?-u:...)`.
+#[macro_export]
+macro_rules! ascii_only {
+    ($part:expr) => {
+        $part.clone().apply($crate::combinators::ascii_only)
+    };
+}
+
+/// A run of whitespace and `marks!`, or the start or end of the text.
+///
+/// Unlike `word_boundary!` it consumes the separators, and it treats
+/// quotes such as `«»` and `’` as word edges.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// let cat = sequence!(custom_word_boundary!(), capturing_group!(escape!("кот")), custom_word_boundary!());
+/// let cat = cat.compile().unwrap();
+/// assert!(cat.is_match("«кот»"));
+/// assert!(!cat.is_match("котёнок"));
+/// ```
+#[macro_export]
+macro_rules! custom_word_boundary {
+    () => {
+        $crate::RegexPart::from($crate::combinators::custom_word_boundary())
+    };
+}
+
+#[macro_export]
+macro_rules! anything {
+    () => {
+        $crate::RegexPart::from($crate::combinators::anything())
+    };
+}
+
+#[macro_export]
+macro_rules! nothing {
+    () => {
+        $crate::RegexPart::from($crate::combinators::nothing())
+    };
+}
+
+#[macro_export]
+macro_rules! word_boundary {
+    () => {
+        $crate::RegexPart::from($crate::combinators::word_boundary())
+    };
+}
+
+#[macro_export]
+macro_rules! whitespace {
+    () => {
+        $crate::RegexPart::from($crate::combinators::whitespace())
+    };
+}
+
+#[macro_export]
+macro_rules! alpha_numeric {
+    () => {
+        $crate::RegexPart::from($crate::combinators::alpha_numeric())
+    };
+}
+
+#[macro_export]
+macro_rules! non_alpha_numeric {
+    () => {
+        $crate::RegexPart::from($crate::combinators::non_alpha_numeric())
+    };
+}
+
+#[macro_export]
+macro_rules! digit {
+    () => {
+        $crate::RegexPart::from($crate::combinators::digit())
+    };
+}
+
+#[macro_export]
+macro_rules! non_digit {
+    () => {
+        $crate::RegexPart::from($crate::combinators::non_digit())
+    };
+}
+
+#[macro_export]
+macro_rules! begin {
+    () => {
+        $crate::RegexPart::from($crate::combinators::begin())
+    };
+}
+
+#[macro_export]
+macro_rules! end {
+    () => {
+        $crate::RegexPart::from($crate::combinators::end())
+    };
+}
+
+#[macro_export]
+macro_rules! cyrillic_letter {
+    () => {
+        $crate::RegexPart::from($crate::combinators::cyrillic_letter())
+    };
+}
+
+#[macro_export]
+macro_rules! punctuation {
+    () => {
+        $crate::RegexPart::from($crate::combinators::punctuation())
+    };
+}
+/// A character class made of the union of its items.
+///
+/// Items are chars, `char` ranges, strings (each of their characters) and
+/// other classes such as `digit!()` or `unicode_class!("Greek")`. Within
+/// a class `anything!()` adds every character, newline included, whatever
+/// the flags.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// let identifier = char_class!['a'..='z', '_', digit!()];
+/// assert_eq!(identifier.source(), r"[a-z_\d]");
+/// assert_eq!(not_class!(identifier).source(), r"[^a-z_\d]");
+/// ```
+#[macro_export]
+macro_rules! char_class {
+    ($($item:expr),+ $(,)?) => {
+        $crate::RegexPart::from($crate::combinators::char_class(vec![
+            $($crate::IntoClass::into_class($item.clone())),+
+        ]))
+    };
+}
+
+/// Every character not in the class. `not_class!(anything!())` matches
+/// nothing.
+#[macro_export]
+macro_rules! not_class {
+    ($class:expr) => {
+        $crate::RegexPart::from($crate::combinators::not_class($crate::IntoClass::into_class($class.clone())))
+    };
+}
+
+/// Characters in both classes, rendered as `[a&&b]`.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// let greek_letter = class_intersection!(unicode_class!("Greek"), unicode_class!("L"));
+/// assert_eq!(greek_letter.source(), r"[\p{Greek}&&\p{L}]");
+/// assert!(greek_letter.compile().unwrap().is_match("λ"));
+/// ```
+#[macro_export]
+macro_rules! class_intersection {
+    ($left:expr, $right:expr) => {
+        $crate::RegexPart::from($crate::combinators::class_intersection(
+            $crate::IntoClass::into_class($left.clone()),
+            $crate::IntoClass::into_class($right.clone()),
+        ))
+    };
+}
+
+/// Characters in the first class but not in the second, rendered as `[a--b]`.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// let consonant = class_difference!(char_class!['a'..='z'], char_class!["aeiou"]);
+/// assert_eq!(consonant.source(), "[[a-z]--[aeiou]]");
+/// ```
+#[macro_export]
+macro_rules! class_difference {
+    ($left:expr, $right:expr) => {
+        $crate::RegexPart::from($crate::combinators::class_difference(
+            $crate::IntoClass::into_class($left.clone()),
+            $crate::IntoClass::into_class($right.clone()),
+        ))
+    };
+}
+
+/// A Unicode property, script or general category, e.g. `"Greek"` or `"L"`.
+#[macro_export]
+macro_rules! unicode_class {
+    ($name:expr) => {
+        $crate::RegexPart::from($crate::combinators::unicode_class(&::std::string::ToString::to_string(&$name)))
+    };
+}
+
+/// Any character of `MARKS`.
+#[macro_export]
+macro_rules! marks {
+    () => {
+        $crate::RegexPart::from($crate::combinators::marks())
+    };
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,206 @@
+use crate::ast::{Class, ClassItem, Flags, Node};
+use crate::RegexPart;
+
+impl RegexPart {
+    /// Rewrites the tree into a smaller equivalent one.
+    ///
+    /// Non-capturing groups are dropped (rendering adds back the ones that
+    /// are needed), adjacent literals are merged, common literal prefixes
+    /// of neighbouring alternatives are factored out and single-character
+    /// alternatives other than `anything!()` become classes, except for
+    /// non-ASCII characters under `ascii_only!`, which classes reject there.
+    /// Matches, their priority and capture group numbers stay the same.
+    ///
+    /// ```
+    /// use RegexLib::*;
+    ///
+    /// let part = any_of!(escape!("foo"), escape!("fob"));
+    /// assert_eq!(part.source(), "foo|fob");
+    /// assert_eq!(part.optimize().source(), "fo[ob]");
+    /// ```
+    pub fn optimize(self) -> Self {
+        optimize(self.into_node(), Flags::default()).into()
+    }
+}
+
+// `active` are the flags in effect around `node`.
+fn optimize(node: Node, active: Flags) -> Node {
+    match node {
+        Node::Group { node, capturing: false, name: None } => optimize(*node, active),
+        Node::Group { node, capturing, name } => {
+            Node::Group { node: Box::new(optimize(*node, active)), capturing, name }
+        }
+        Node::Flags { node, flags } => match optimize(*node, active.then(flags)) {
+            node if flags.is_empty() => node,
+            Node::Flags { node, flags: inner } => Node::Flags { node, flags: flags.then(inner) },
+            node => Node::Flags { node: Box::new(node), flags },
+        },
+        Node::Look { node, ahead, negated } => {
+            Node::Look { node: Box::new(optimize(*node, active)), ahead, negated }
+        }
+        Node::Repeat { node, min: 1, max: Some(1), .. } => optimize(*node, active),
+        Node::Repeat { node, min, max, greedy } => {
+            Node::Repeat { node: Box::new(optimize(*node, active)), min, max, greedy }
+        }
+        Node::Concat(nodes) => concat(nodes.into_iter().map(|node| optimize(node, active)).collect()),
+        Node::Alt(nodes) => alternation(nodes.into_iter().map(|node| optimize(node, active)).collect(), active),
+        Node::Class(Class::Set { items, negated: false }) if matches!(items.as_slice(), [ClassItem::Char(_)]) => {
+            match items[0] {
+                ClassItem::Char(c) => Node::Literal(c.to_string()),
+                _ => unreachable!(),
+            }
+        }
+        node => node,
+    }
+}
+
+// Flattens nested sequences, drops empty parts and merges literals.
+fn concat(nodes: Vec<Node>) -> Node {
+    let mut flat = Vec::new();
+    for node in nodes {
+        match node {
+            Node::Concat(nested) => flat.extend(nested),
+            Node::Empty => {}
+            Node::Literal(text) if text.is_empty() => {}
+            node => flat.push(node),
+        }
+    }
+    let mut merged: Vec<Node> = Vec::with_capacity(flat.len());
+    for node in flat {
+        match (merged.last_mut(), node) {
+            (Some(Node::Literal(text)), Node::Literal(next)) => text.push_str(&next),
+            (_, node) => merged.push(node),
+        }
+    }
+    match merged.len() {
+        0 => Node::Empty,
+        1 => merged.pop().unwrap(),
+        _ => Node::Concat(merged),
+    }
+}
+
+// Only neighbouring alternatives are rewritten, so the order in which the
+// branches are tried never changes.
+fn alternation(nodes: Vec<Node>, active: Flags) -> Node {
+    let mut flat = Vec::new();
+    for node in nodes {
+        match node {
+            Node::Alt(nested) => flat.extend(nested),
+            node => flat.push(node),
+        }
+    }
+    let mut branches = factor_prefixes(flat, active);
+    branches = merge_single_chars(branches, active);
+    match branches.len() {
+        1 => branches.pop().unwrap(),
+        _ => Node::Alt(branches),
+    }
+}
+
+fn factor_prefixes(branches: Vec<Node>, active: Flags) -> Vec<Node> {
+    let mut result = Vec::new();
+    let mut branches = branches.into_iter().peekable();
+    while let Some(branch) = branches.next() {
+        let Some(first) = leading_literal(&branch).and_then(|text| text.chars().next()) else {
+            result.push(branch);
+            continue;
+        };
+        let mut run = vec![branch];
+        while let Some(next) = branches.next_if(|next| leading_literal(next).is_some_and(|text| text.starts_with(first)))
+        {
+            run.push(next);
+        }
+        if run.len() == 1 {
+            result.extend(run);
+            continue;
+        }
+        let prefix = run
+            .iter()
+            .filter_map(leading_literal)
+            .reduce(common_prefix)
+            .unwrap()
+            .to_string();
+        let rests = run.into_iter().map(|branch| strip_prefix(branch, prefix.len())).collect();
+        result.push(concat(vec![Node::Literal(prefix), alternation(rests, active)]));
+    }
+    result
+}
+
+fn leading_literal(node: &Node) -> Option<&str> {
+    match node {
+        Node::Literal(text) => Some(text),
+        Node::Concat(nodes) => match nodes.first() {
+            Some(Node::Literal(text)) => Some(text),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn common_prefix<'a>(left: &'a str, right: &str) -> &'a str {
+    let len = left
+        .char_indices()
+        .zip(right.chars())
+        .find(|((_, l), r)| l != r)
+        .map_or(left.len().min(right.len()), |((index, _), _)| index);
+    &left[..len]
+}
+
+// Removes the first `len` bytes of the leading literal.
+fn strip_prefix(node: Node, len: usize) -> Node {
+    match node {
+        Node::Literal(text) => concat(vec![Node::Literal(text[len..].to_string())]),
+        Node::Concat(mut nodes) => {
+            if let Node::Literal(text) = &mut nodes[0] {
+                text.drain(..len);
+            }
+            concat(nodes)
+        }
+        _ => unreachable!("only branches with a leading literal are factored"),
+    }
+}
+
+// Neighbouring branches that each match exactly one character can not
+// compete with each other, so they fold into one class.
+fn merge_single_chars(branches: Vec<Node>, active: Flags) -> Vec<Node> {
+    let mut result = Vec::new();
+    let mut run = Vec::new();
+    for branch in branches {
+        match single_char_class(&branch, active) {
+            Some(class) => run.push((branch, class)),
+            None => {
+                flush(&mut run, &mut result);
+                result.push(branch);
+            }
+        }
+    }
+    flush(&mut run, &mut result);
+    result
+}
+
+fn flush(run: &mut Vec<(Node, Class)>, result: &mut Vec<Node>) {
+    match run.len() {
+        0 => {}
+        1 => result.push(run.pop().unwrap().0),
+        _ => result.push(Node::Class(Class::union(run.drain(..).map(|(_, class)| class)))),
+    }
+}
+
+fn single_char_class(node: &Node, active: Flags) -> Option<Class> {
+    match node {
+        Node::Literal(text) => {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                // Without `u` a non-ASCII literal is fine but the same
+                // character in a class is an error.
+                (Some(c), None) if !c.is_ascii() && active.unicode == Some(false) => None,
+                (Some(c), None) => Some(Class::Set { items: vec![ClassItem::Char(c)], negated: false }),
+                _ => None,
+            }
+        }
+        // `.` depends on the `s` flag, which a class can not express.
+        Node::Class(Class::Any) => None,
+        Node::Class(class) => Some(class.clone()),
+        _ => None,
+    }
+}
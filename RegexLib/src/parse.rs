@@ -0,0 +1,279 @@
+use std::error::Error;
+use std::fmt;
+
+use regex_syntax::ast::{self, Ast, AssertionKind, ClassAsciiKind, ClassPerlKind, ClassSetBinaryOpKind, ClassSetItem};
+use regex_syntax::ast::{ClassUnicodeKind, Flag, GroupKind, RepetitionKind, RepetitionRange};
+use regex_syntax::hir::translate::Translator;
+
+use crate::ast::{Anchor, Class, ClassItem, Flags, Node, PerlClass};
+use crate::RegexPart;
+
+/// Why `RegexPart::parse` rejected a pattern.
+#[derive(Debug, Clone)]
+pub enum ParseError {
+    Syntax(Box<regex_syntax::Error>),
+    /// The pattern is valid but uses a construct `RegexPart` can not hold.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Syntax(error) => write!(f, "invalid regex: {}", error),
+            ParseError::Unsupported(construct) => write!(f, "{} is not supported by RegexPart", construct),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Syntax(error) => Some(&**error),
+            ParseError::Unsupported(_) => None,
+        }
+    }
+}
+
+impl From<regex_syntax::Error> for ParseError {
+    fn from(error: regex_syntax::Error) -> Self {
+        ParseError::Syntax(Box::new(error))
+    }
+}
+
+impl RegexPart {
+    /// Parses a pattern in `regex` syntax into combinators.
+    ///
+    /// Groups, flags, classes and repetitions keep their structure;
+    /// non-capturing groups without flags are dropped and rendering adds
+    /// them back where needed. Assertions without a combinator, such as
+    /// `\A` or `\b{start}`, become `raw!` parts. The `R` flag is not
+    /// supported.
+    ///
+    /// ```
+    /// use RegexLib::*;
+    ///
+    /// let part = RegexPart::parse(r"v(\d+)\.(\d+)").unwrap();
+    /// assert_eq!(
+    ///     part,
+    ///     sequence!(
+    ///         escape!("v"),
+    ///         capturing_group!(one_or_more!(digit!())),
+    ///         escape!("."),
+    ///         capturing_group!(one_or_more!(digit!()))
+    ///     )
+    /// );
+    /// assert!(RegexPart::parse("a(b").is_err());
+    /// ```
+    pub fn parse(pattern: &str) -> Result<RegexPart, ParseError> {
+        let ast = ast::parse::Parser::new().parse(pattern).map_err(regex_syntax::Error::from)?;
+        // The AST parser accepts some patterns that can not be compiled,
+        // such as unknown Unicode classes.
+        Translator::new().translate(pattern, &ast).map_err(regex_syntax::Error::from)?;
+        Ok(Converter { pattern }.body(&ast)?.into())
+    }
+}
+
+struct Converter<'p> {
+    pattern: &'p str,
+}
+
+impl Converter<'_> {
+    // Contents of a group, or of the whole pattern.
+    fn body(&self, ast: &Ast) -> Result<Node, ParseError> {
+        match ast {
+            Ast::Alternation(alternation) => {
+                // `(?flags)` holds until the end of the group, including
+                // the branches after the one it appears in.
+                let mut carried = Flags::default();
+                let mut branches = Vec::new();
+                for branch in &alternation.asts {
+                    let (node, set) = self.sequence(branch)?;
+                    branches.push(with_flags(node, carried));
+                    carried = carried.then(set);
+                }
+                Ok(Node::Alt(branches))
+            }
+            ast => Ok(self.sequence(ast)?.0),
+        }
+    }
+
+    // Returns the sequence and the flags set by a `(?flags)` inside it.
+    fn sequence(&self, ast: &Ast) -> Result<(Node, Flags), ParseError> {
+        match ast {
+            Ast::Concat(concat) => self.items(&concat.asts),
+            ast => self.items(std::slice::from_ref(ast)),
+        }
+    }
+
+    fn items(&self, asts: &[Ast]) -> Result<(Node, Flags), ParseError> {
+        let mut nodes = Vec::new();
+        for (i, ast) in asts.iter().enumerate() {
+            match ast {
+                Ast::Flags(set) => {
+                    let flags = self.flags(&set.flags)?;
+                    let (rest, inner) = self.items(&asts[i + 1..])?;
+                    nodes.push(with_flags(rest, flags));
+                    return Ok((concat(nodes), flags.then(inner)));
+                }
+                Ast::Literal(literal) => match nodes.last_mut() {
+                    Some(Node::Literal(text)) => text.push(literal.c),
+                    _ => nodes.push(Node::Literal(literal.c.to_string())),
+                },
+                ast => nodes.push(self.node(ast)?),
+            }
+        }
+        Ok((concat(nodes), Flags::default()))
+    }
+
+    fn node(&self, ast: &Ast) -> Result<Node, ParseError> {
+        Ok(match ast {
+            Ast::Empty(_) => Node::Empty,
+            Ast::Literal(literal) => Node::Literal(literal.c.to_string()),
+            Ast::Dot(_) => Node::Class(Class::Any),
+            Ast::Assertion(assertion) => match assertion.kind {
+                AssertionKind::StartLine => Node::Anchor(Anchor::Start),
+                AssertionKind::EndLine => Node::Anchor(Anchor::End),
+                AssertionKind::WordBoundary => Node::Anchor(Anchor::WordBoundary),
+                AssertionKind::NotWordBoundary => Node::Anchor(Anchor::NotWordBoundary),
+                _ => Node::Raw(self.pattern[assertion.span.start.offset..assertion.span.end.offset].to_string()),
+            },
+            Ast::ClassUnicode(class) => Node::Class(unicode(class)),
+            Ast::ClassPerl(class) => Node::Class(perl(class)),
+            Ast::ClassBracketed(class) => Node::Class(bracketed(class)),
+            Ast::Repetition(repetition) => {
+                let (min, max) = match &repetition.op.kind {
+                    RepetitionKind::ZeroOrOne => (0, Some(1)),
+                    RepetitionKind::ZeroOrMore => (0, None),
+                    RepetitionKind::OneOrMore => (1, None),
+                    RepetitionKind::Range(RepetitionRange::Exactly(count)) => (*count, Some(*count)),
+                    RepetitionKind::Range(RepetitionRange::AtLeast(min)) => (*min, None),
+                    RepetitionKind::Range(RepetitionRange::Bounded(min, max)) => (*min, Some(*max)),
+                };
+                let node = Box::new(self.node(&repetition.ast)?);
+                Node::Repeat { node, min, max, greedy: repetition.greedy }
+            }
+            Ast::Group(group) => {
+                let node = self.body(&group.ast)?;
+                match &group.kind {
+                    GroupKind::CaptureIndex(_) => Node::Group { node: Box::new(node), capturing: true, name: None },
+                    GroupKind::CaptureName { name, .. } => {
+                        Node::Group { node: Box::new(node), capturing: true, name: Some(name.name.clone()) }
+                    }
+                    GroupKind::NonCapturing(flags) => with_flags(node, self.flags(flags)?),
+                }
+            }
+            Ast::Flags(_) | Ast::Alternation(_) | Ast::Concat(_) => self.body(ast)?,
+        })
+    }
+
+    fn flags(&self, flags: &ast::Flags) -> Result<Flags, ParseError> {
+        if flags.flag_state(Flag::CRLF).is_some() {
+            return Err(ParseError::Unsupported("the `R` flag"));
+        }
+        Ok(Flags {
+            case_insensitive: flags.flag_state(Flag::CaseInsensitive),
+            multi_line: flags.flag_state(Flag::MultiLine),
+            dot_matches_new_line: flags.flag_state(Flag::DotMatchesNewLine),
+            ignore_whitespace: flags.flag_state(Flag::IgnoreWhitespace),
+            swap_greed: flags.flag_state(Flag::SwapGreed),
+            unicode: flags.flag_state(Flag::Unicode),
+        })
+    }
+}
+
+fn with_flags(node: Node, flags: Flags) -> Node {
+    if flags.is_empty() {
+        node
+    } else {
+        Node::Flags { node: Box::new(node), flags }
+    }
+}
+
+fn concat(mut nodes: Vec<Node>) -> Node {
+    match nodes.len() {
+        0 => Node::Empty,
+        1 => nodes.pop().unwrap(),
+        _ => Node::Concat(nodes),
+    }
+}
+
+fn perl(class: &ast::ClassPerl) -> Class {
+    let perl = match class.kind {
+        ClassPerlKind::Digit => PerlClass::Digit,
+        ClassPerlKind::Space => PerlClass::Space,
+        ClassPerlKind::Word => PerlClass::Word,
+    };
+    Class::Perl { class: perl, negated: class.negated }
+}
+
+fn unicode(class: &ast::ClassUnicode) -> Class {
+    let name = match &class.kind {
+        ClassUnicodeKind::OneLetter(letter) => letter.to_string(),
+        ClassUnicodeKind::Named(name) => name.clone(),
+        ClassUnicodeKind::NamedValue { name, value, .. } => format!("{}={}", name, value),
+    };
+    Class::Unicode { name, negated: class.is_negated() }
+}
+
+fn bracketed(class: &ast::ClassBracketed) -> Class {
+    let set = class_set(&class.kind);
+    if class.negated {
+        set.negate()
+    } else {
+        set
+    }
+}
+
+fn class_set(set: &ast::ClassSet) -> Class {
+    match set {
+        ast::ClassSet::Item(item) => class_item(item),
+        ast::ClassSet::BinaryOp(operation) => {
+            let (left, right) = (class_set(&operation.lhs), class_set(&operation.rhs));
+            match operation.kind {
+                ClassSetBinaryOpKind::Intersection => left.intersection(right),
+                ClassSetBinaryOpKind::Difference => left.difference(right),
+                ClassSetBinaryOpKind::SymmetricDifference => {
+                    Class::union([left.clone().difference(right.clone()), right.difference(left)])
+                }
+            }
+        }
+    }
+}
+
+fn class_item(item: &ClassSetItem) -> Class {
+    match item {
+        ClassSetItem::Empty(_) => Class::Set { items: Vec::new(), negated: false },
+        ClassSetItem::Literal(literal) => Class::Set { items: vec![ClassItem::Char(literal.c)], negated: false },
+        ClassSetItem::Range(range) => {
+            Class::Set { items: vec![ClassItem::Range(range.start.c, range.end.c)], negated: false }
+        }
+        ClassSetItem::Ascii(class) => {
+            let items = ascii(&class.kind).iter().map(|&(from, to)| ClassItem::Range(from, to)).collect();
+            Class::Set { items, negated: class.negated }
+        }
+        ClassSetItem::Unicode(class) => unicode(class),
+        ClassSetItem::Perl(class) => perl(class),
+        ClassSetItem::Bracketed(class) => bracketed(class),
+        ClassSetItem::Union(union) => Class::union(union.items.iter().map(class_item)),
+    }
+}
+
+// The POSIX classes as `regex` defines them.
+fn ascii(kind: &ClassAsciiKind) -> &'static [(char, char)] {
+    match kind {
+        ClassAsciiKind::Alnum => &[('0', '9'), ('A', 'Z'), ('a', 'z')],
+        ClassAsciiKind::Alpha => &[('A', 'Z'), ('a', 'z')],
+        ClassAsciiKind::Ascii => &[('\0', '\x7F')],
+        ClassAsciiKind::Blank => &[('\t', '\t'), (' ', ' ')],
+        ClassAsciiKind::Cntrl => &[('\0', '\x1F'), ('\x7F', '\x7F')],
+        ClassAsciiKind::Digit => &[('0', '9')],
+        ClassAsciiKind::Graph => &[('!', '~')],
+        ClassAsciiKind::Lower => &[('a', 'z')],
+        ClassAsciiKind::Print => &[(' ', '~')],
+        ClassAsciiKind::Punct => &[('!', '/'), (':', '@'), ('[', '`'), ('{', '~')],
+        ClassAsciiKind::Space => &[('\t', '\r'), (' ', ' ')],
+        ClassAsciiKind::Upper => &[('A', 'Z')],
+        ClassAsciiKind::Word => &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')],
+        ClassAsciiKind::Xdigit => &[('0', '9'), ('A', 'F'), ('a', 'f')],
+    }
+}
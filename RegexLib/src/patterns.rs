@@ -0,0 +1,225 @@
+//! Building blocks for formats that come up in every service.
+//!
+//! The patterns are unanchored and contain no capturing groups, so they
+//! compose with `sequence!` and `any_of!`; wrap them in `begin!()`/`end!()`
+//! or word boundaries to match whole inputs. Digits are ASCII only.
+//!
+//! ```
+//! use RegexLib::{begin, end, escape, patterns, sequence};
+//!
+//! let release = sequence!(begin!(), patterns::semver(), escape!(" "), patterns::iso_date(), end!());
+//! assert!(release.compile().unwrap().is_match("1.4.0-rc.1 2024-02-29"));
+//! ```
+
+use crate::{
+    any_of, at_least, between, char_class, escape, exactly, not_class, one_or_more, optional, sequence, whitespace,
+    zero_or_more, RegexPart,
+};
+
+fn digit() -> RegexPart {
+    char_class!['0'..='9']
+}
+
+fn hex_digit() -> RegexPart {
+    char_class!['0'..='9', 'a'..='f', 'A'..='F']
+}
+
+fn sign() -> RegexPart {
+    char_class!["+-"]
+}
+
+// `0`, or digits without a leading zero.
+fn number() -> RegexPart {
+    any_of!(escape!("0"), sequence!(char_class!['1'..='9'], zero_or_more!(digit())))
+}
+
+// Two digits from `00` up to `max`, which must be at least `20`.
+fn two_digits_up_to(max: u32) -> RegexPart {
+    let (tens, ones) = (char::from_digit(max / 10, 10).unwrap(), char::from_digit(max % 10, 10).unwrap());
+    let below = char::from_digit(max / 10 - 1, 10).unwrap();
+    any_of!(
+        sequence!(char_class!['0'..=below], digit()),
+        sequence!(escape!(tens.to_string()), char_class!['0'..=ones])
+    )
+}
+
+/// Optionally signed decimal integer: `42`, `-7`, `+0`.
+pub fn integer() -> RegexPart {
+    sequence!(optional!(sign()), one_or_more!(digit()))
+}
+
+/// Decimal number with a fraction, an exponent or both: `3.14`, `.5`,
+/// `-1e10`, `6.02E+23`. Plain integers are left to `integer`.
+pub fn float() -> RegexPart {
+    let exponent = sequence!(char_class!["eE"], optional!(sign()), one_or_more!(digit()));
+    let fraction = any_of!(
+        sequence!(one_or_more!(digit()), escape!("."), zero_or_more!(digit())),
+        sequence!(escape!("."), one_or_more!(digit()))
+    );
+    sequence!(
+        optional!(sign()),
+        any_of!(
+            sequence!(fraction, optional!(exponent)),
+            sequence!(one_or_more!(digit()), exponent)
+        )
+    )
+}
+
+/// Calendar date `YYYY-MM-DD`. Day numbers are checked against 31, not
+/// against the month.
+pub fn iso_date() -> RegexPart {
+    let month = any_of!(sequence!(escape!("0"), char_class!['1'..='9']), sequence!(escape!("1"), char_class!['0'..='2']));
+    let day = any_of!(
+        sequence!(escape!("0"), char_class!['1'..='9']),
+        sequence!(char_class!["12"], digit()),
+        sequence!(escape!("3"), char_class!["01"])
+    );
+    sequence!(exactly!(digit(), 4), escape!("-"), month, escape!("-"), day)
+}
+
+/// Time of day `hh:mm`, `hh:mm:ss` or `hh:mm:ss.fff`, optionally followed
+/// by `Z` or an offset such as `+03:00`. A leap second `60` is accepted.
+pub fn iso_time() -> RegexPart {
+    let hour = two_digits_up_to(23);
+    let minute = sequence!(char_class!['0'..='5'], digit());
+    let second = any_of!(minute.clone(), escape!("60"));
+    let fraction = sequence!(char_class![".,"], one_or_more!(digit()));
+    let offset = any_of!(
+        escape!("Z"),
+        sequence!(sign(), hour.clone(), optional!(escape!(":")), minute.clone())
+    );
+    sequence!(
+        hour,
+        escape!(":"),
+        minute,
+        optional!(sequence!(escape!(":"), second, optional!(fraction))),
+        optional!(offset)
+    )
+}
+
+/// `iso_date` and `iso_time` joined by `T`: `2024-02-29T13:37:00Z`.
+pub fn iso_datetime() -> RegexPart {
+    sequence!(iso_date(), escape!("T"), iso_time())
+}
+
+/// Dotted-quad IPv4 address with octets up to 255 and no leading zeros.
+pub fn ipv4() -> RegexPart {
+    let octet = any_of!(
+        sequence!(escape!("25"), char_class!['0'..='5']),
+        sequence!(escape!("2"), char_class!['0'..='4'], digit()),
+        sequence!(escape!("1"), digit(), digit()),
+        sequence!(char_class!['1'..='9'], digit()),
+        digit()
+    );
+    sequence!(octet.clone(), exactly!(sequence!(escape!("."), octet), 3))
+}
+
+/// IPv6 address in full, `::`-compressed or IPv4-suffixed form.
+/// Zone indices (`%eth0`) are not included.
+pub fn ipv6() -> RegexPart {
+    let group = between!(hex_digit(), 1, 4);
+    let groups = |count: u32| exactly!(sequence!(group.clone(), escape!(":")), count);
+    let mut forms = vec![
+        sequence!(groups(7), group.clone()),
+        sequence!(groups(6), ipv4()),
+    ];
+    // `left` groups before the `::`, up to `7 - left` after it.
+    for left in (0..=7).rev() {
+        let head = match left {
+            0 => escape!("::"),
+            _ => sequence!(groups(left), escape!(":")),
+        };
+        if left == 7 {
+            forms.push(head);
+            continue;
+        }
+        let tail = sequence!(group.clone(), between!(sequence!(escape!(":"), group.clone()), 0, 6 - left));
+        let tail = match left {
+            0..=5 => any_of!(sequence!(between!(sequence!(group.clone(), escape!(":")), 0, 5 - left), ipv4()), tail),
+            _ => tail,
+        };
+        forms.push(sequence!(head, optional!(tail)));
+    }
+    RegexPart::any_of(forms)
+}
+
+/// Pragmatic e-mail address: a dot-atom local part and a domain with a
+/// top-level label of at least two letters. Quoted local parts and IP
+/// literals are not supported.
+pub fn email() -> RegexPart {
+    let atom = one_or_more!(char_class!['a'..='z', 'A'..='Z', '0'..='9', "!#$%&'*+/=?^_`{|}~-"]);
+    let local = sequence!(atom.clone(), zero_or_more!(sequence!(escape!("."), atom)));
+    sequence!(local, escape!("@"), domain())
+}
+
+// Dot-separated labels ending in an alphabetic top-level label.
+fn domain() -> RegexPart {
+    let alphanumeric = char_class!['a'..='z', 'A'..='Z', '0'..='9'];
+    let label = sequence!(
+        alphanumeric.clone(),
+        optional!(sequence!(between!(char_class![alphanumeric.clone(), '-'], 0, 61), alphanumeric))
+    );
+    sequence!(
+        one_or_more!(sequence!(label, escape!("."))),
+        at_least!(char_class!['a'..='z', 'A'..='Z'], 2)
+    )
+}
+
+/// `http`, `https` or `ftp` URL with a domain, `localhost`, IPv4 or
+/// bracketed IPv6 host, an optional port, path, query and fragment.
+pub fn url() -> RegexPart {
+    let host = any_of!(
+        domain(),
+        escape!("localhost"),
+        ipv4(),
+        sequence!(escape!("["), ipv6(), escape!("]"))
+    );
+    let port = sequence!(escape!(":"), between!(digit(), 1, 5));
+    let path = sequence!(escape!("/"), zero_or_more!(not_class!(char_class![whitespace!(), "?#"])));
+    let query = sequence!(escape!("?"), zero_or_more!(not_class!(char_class![whitespace!(), '#'])));
+    let fragment = sequence!(escape!("#"), zero_or_more!(not_class!(whitespace!())));
+    sequence!(
+        any_of!(escape!("https"), escape!("http"), escape!("ftp")),
+        escape!("://"),
+        host,
+        optional!(port),
+        optional!(path),
+        optional!(query),
+        optional!(fragment)
+    )
+}
+
+/// UUID in the canonical `8-4-4-4-12` hex form, any version.
+pub fn uuid() -> RegexPart {
+    let hex = |count| exactly!(hex_digit(), count);
+    sequence!(hex(8), escape!("-"), hex(4), escape!("-"), hex(4), escape!("-"), hex(4), escape!("-"), hex(12))
+}
+
+/// Semantic version `MAJOR.MINOR.PATCH` with optional pre-release and
+/// build metadata, following the grammar on semver.org.
+pub fn semver() -> RegexPart {
+    let alphanumeric = char_class!['0'..='9', 'a'..='z', 'A'..='Z', '-'];
+    let prerelease_part = any_of!(
+        number(),
+        sequence!(zero_or_more!(digit()), char_class!['a'..='z', 'A'..='Z', '-'], zero_or_more!(alphanumeric.clone()))
+    );
+    let dotted = |part: RegexPart| sequence!(part.clone(), zero_or_more!(sequence!(escape!("."), part)));
+    sequence!(
+        number(),
+        escape!("."),
+        number(),
+        escape!("."),
+        number(),
+        optional!(sequence!(escape!("-"), dotted(prerelease_part))),
+        optional!(sequence!(escape!("+"), dotted(one_or_more!(alphanumeric))))
+    )
+}
+
+/// String between two `quote` characters in which a backslash escapes
+/// the following character, e.g. `"say \"hi\""` for `'"'`.
+pub fn quoted_string(quote: char) -> RegexPart {
+    let quote_text = quote.to_string();
+    let plain = not_class!(char_class![quote, '\\']);
+    let escaped = sequence!(escape!("\\"), char_class![whitespace!(), not_class!(whitespace!())]);
+    sequence!(escape!(quote_text), zero_or_more!(any_of!(plain, escaped)), escape!(quote_text))
+}
@@ -0,0 +1,330 @@
+use std::fmt::Write;
+
+use crate::ast::{Flags, Node};
+use crate::explain::{describe_anchor, describe_class, describe_flags, describe_group};
+use crate::RegexPart;
+
+const CHAR_WIDTH: f64 = 8.0;
+const BOX_HEIGHT: f64 = 22.0;
+// Horizontal track between the items of a sequence.
+const GAP: f64 = 10.0;
+// Vertical space between stacked tracks.
+const SPACING: f64 = 10.0;
+// Room for the curves where tracks split and join.
+const TURN: f64 = 20.0;
+const LABEL_HEIGHT: f64 = 14.0;
+const MARGIN: f64 = 20.0;
+
+const STYLE: &str = "path { fill: none; stroke: #333; stroke-width: 2; } \
+rect { fill: #fff; stroke: #333; stroke-width: 2; } \
+rect.group { fill: none; stroke: #888; stroke-width: 1; stroke-dasharray: 4 3; } \
+text { font: 13px monospace; text-anchor: middle; } \
+text.label { font-size: 11px; fill: #555; text-anchor: start; }";
+
+impl RegexPart {
+    /// Draws the pattern as a railroad diagram and returns a standalone
+    /// SVG document. Literals are rounded boxes, classes and anchors square
+    /// boxes, and groups, lookaround and flags dashed frames.
+    ///
+    /// ```
+    /// use RegexLib::*;
+    ///
+    /// let svg = sequence!(one_or_more!(digit!()), optional!(escape!("%"))).to_railroad_svg();
+    /// assert!(svg.starts_with("<svg"));
+    /// assert!(svg.contains(">a digit</text>"));
+    /// ```
+    pub fn to_railroad_svg(&self) -> String {
+        let mut groups = 0;
+        let diagram = Diagram::from_node(self.node(), Flags::default(), &mut groups);
+        let width = diagram.width() + 2.0 * MARGIN;
+        let height = diagram.up() + diagram.down() + 2.0 * MARGIN;
+        let y = MARGIN + diagram.up();
+
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height
+        )
+        .unwrap();
+        write!(svg, "<style>{}</style>", STYLE).unwrap();
+        // Entry and exit markers.
+        let end = MARGIN + diagram.width();
+        for x in [MARGIN / 2.0, end + MARGIN / 2.0] {
+            write!(svg, r#"<path d="M {} {} V {}"/>"#, x, y - 8.0, y + 8.0).unwrap();
+        }
+        line(MARGIN / 2.0, MARGIN, y, &mut svg);
+        diagram.draw(MARGIN, y, &mut svg);
+        line(end, end + MARGIN / 2.0, y, &mut svg);
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+// Every diagram is drawn on a horizontal track at its baseline `y`, with
+// `up` and `down` the room it needs above and below that track.
+enum Diagram {
+    Terminal(String),
+    NonTerminal(String),
+    Skip,
+    Sequence(Vec<Diagram>),
+    Choice(Vec<Diagram>),
+    Loop { item: Box<Diagram>, label: Option<String> },
+    Frame { item: Box<Diagram>, label: String },
+}
+
+impl Diagram {
+    fn from_node(node: &Node, flags: Flags, groups: &mut u32) -> Diagram {
+        match node {
+            Node::Empty => Diagram::Skip,
+            Node::Literal(text) => Diagram::Terminal(format!("{:?}", text)),
+            Node::Raw(source) => Diagram::NonTerminal(source.clone()),
+            Node::Class(class) => Diagram::NonTerminal(describe_class(class, flags)),
+            Node::Anchor(anchor) => Diagram::NonTerminal(describe_anchor(*anchor).to_string()),
+            Node::Concat(nodes) => {
+                Diagram::Sequence(nodes.iter().map(|node| Diagram::from_node(node, flags, groups)).collect())
+            }
+            Node::Alt(nodes) if nodes.is_empty() => Diagram::NonTerminal("never matches".to_string()),
+            Node::Alt(nodes) => {
+                Diagram::Choice(nodes.iter().map(|node| Diagram::from_node(node, flags, groups)).collect())
+            }
+            Node::Repeat { node, min, max, greedy } => {
+                let item = Diagram::from_node(node, flags, groups);
+                let mut label = match (min, max) {
+                    (0, Some(1)) | (0, None) | (1, None) => None,
+                    (min, None) => Some(format!("at least {} times", min)),
+                    (min, Some(max)) if min == max => Some(format!("{} times", min)),
+                    (min, Some(max)) => Some(format!("{} to {} times", min, max)),
+                };
+                if !greedy {
+                    label = Some(match label {
+                        Some(label) => format!("{}, as few as possible", label),
+                        None => "as few as possible".to_string(),
+                    });
+                }
+                let repeated = match max {
+                    Some(1) => item,
+                    _ => Diagram::Loop { item: Box::new(item), label: label.take() },
+                };
+                let repeated = match label {
+                    // A lazy `?` has no loop to carry the label.
+                    Some(label) => Diagram::Frame { item: Box::new(repeated), label },
+                    None => repeated,
+                };
+                match min {
+                    0 => Diagram::Choice(vec![Diagram::Skip, repeated]),
+                    _ => repeated,
+                }
+            }
+            Node::Group { node, capturing: false, name: None } => Diagram::from_node(node, flags, groups),
+            Node::Group { node, name, .. } => {
+                *groups += 1;
+                let label = match name {
+                    Some(name) => format!("group {} {:?}", groups, name),
+                    None => format!("group {}", groups),
+                };
+                Diagram::Frame { item: Box::new(Diagram::from_node(node, flags, groups)), label }
+            }
+            Node::Look { node, ahead, negated } => {
+                let label = match (ahead, negated) {
+                    (true, false) => "followed by",
+                    (true, true) => "not followed by",
+                    (false, false) => "preceded by",
+                    (false, true) => "not preceded by",
+                };
+                Diagram::Frame { item: Box::new(Diagram::from_node(node, flags, groups)), label: label.to_string() }
+            }
+            Node::Flags { node, flags: inner } => {
+                let item = Diagram::from_node(node, flags.then(*inner), groups);
+                Diagram::Frame { item: Box::new(item), label: describe_flags(inner) }
+            }
+            Node::Backref(group) => Diagram::NonTerminal(format!("same as {}", describe_group(group))),
+        }
+    }
+
+    fn width(&self) -> f64 {
+        match self {
+            Diagram::Terminal(text) | Diagram::NonTerminal(text) => text_width(text) + 20.0,
+            Diagram::Skip => 0.0,
+            Diagram::Sequence(items) => {
+                items.iter().map(Diagram::width).sum::<f64>() + GAP * items.len().saturating_sub(1) as f64
+            }
+            Diagram::Choice(items) => items.iter().map(Diagram::width).fold(0.0, f64::max) + 2.0 * TURN,
+            Diagram::Loop { item, label } => {
+                let label = label.as_deref().map_or(0.0, text_width);
+                item.width().max(label) + 2.0 * TURN
+            }
+            Diagram::Frame { item, label } => item.width().max(text_width(label) * 11.0 / 13.0 + 10.0) + 2.0 * GAP,
+        }
+    }
+
+    fn up(&self) -> f64 {
+        match self {
+            Diagram::Terminal(_) | Diagram::NonTerminal(_) => BOX_HEIGHT / 2.0,
+            Diagram::Skip => 0.0,
+            Diagram::Sequence(items) => items.iter().map(Diagram::up).fold(0.0, f64::max),
+            Diagram::Choice(items) => items.first().map_or(0.0, Diagram::up),
+            Diagram::Loop { item, .. } => item.up(),
+            Diagram::Frame { item, .. } => item.up() + SPACING + LABEL_HEIGHT,
+        }
+    }
+
+    fn down(&self) -> f64 {
+        match self {
+            Diagram::Terminal(_) | Diagram::NonTerminal(_) => BOX_HEIGHT / 2.0,
+            Diagram::Skip => 0.0,
+            Diagram::Sequence(items) => items.iter().map(Diagram::down).fold(0.0, f64::max),
+            Diagram::Choice(items) => {
+                let first = items.first().map_or(0.0, Diagram::down);
+                first + items.iter().skip(1).map(|item| SPACING + item.up() + item.down()).sum::<f64>()
+            }
+            Diagram::Loop { item, label } => {
+                Diagram::loop_depth(item) + label.as_ref().map_or(0.0, |_| LABEL_HEIGHT)
+            }
+            Diagram::Frame { item, .. } => item.down() + SPACING,
+        }
+    }
+
+    // Distance from the track down to the return path of a loop.
+    fn loop_depth(item: &Diagram) -> f64 {
+        (item.down() + SPACING).max(TURN)
+    }
+
+    fn draw(&self, x: f64, y: f64, svg: &mut String) {
+        match self {
+            Diagram::Terminal(text) => draw_box(x, y, self.width(), text, true, svg),
+            Diagram::NonTerminal(text) => draw_box(x, y, self.width(), text, false, svg),
+            Diagram::Skip => {}
+            Diagram::Sequence(items) => {
+                let mut cursor = x;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        line(cursor, cursor + GAP, y, svg);
+                        cursor += GAP;
+                    }
+                    item.draw(cursor, y, svg);
+                    cursor += item.width();
+                }
+            }
+            Diagram::Choice(items) => {
+                let width = self.width();
+                let inner = width - 2.0 * TURN;
+                let (left, right) = (x + TURN / 2.0, x + width - TURN / 2.0);
+                let mut track = y;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        track += items[i - 1].down() + SPACING + item.up();
+                        write!(
+                            svg,
+                            r#"<path d="M {x} {y} Q {l} {y} {l} {y1} V {t0} Q {l} {t} {lt} {t} M {rt} {t} Q {r} {t} {r} {t0} V {y1} Q {r} {y} {end} {y}"/>"#,
+                            x = x,
+                            y = y,
+                            l = left,
+                            y1 = y + TURN / 2.0,
+                            t0 = track - TURN / 2.0,
+                            t = track,
+                            lt = x + TURN,
+                            rt = x + TURN + inner,
+                            r = right,
+                            end = x + width
+                        )
+                        .unwrap();
+                    } else {
+                        line(x, x + TURN, y, svg);
+                        line(x + TURN + inner, x + width, y, svg);
+                    }
+                    item.draw(x + TURN, track, svg);
+                    line(x + TURN + item.width(), x + TURN + inner, track, svg);
+                }
+            }
+            Diagram::Loop { item, label } => {
+                let width = self.width();
+                let inner = width - 2.0 * TURN;
+                let back = y + Diagram::loop_depth(item);
+                let (left, right) = (x + TURN / 2.0, x + width - TURN / 2.0);
+                line(x, x + TURN, y, svg);
+                item.draw(x + TURN, y, svg);
+                line(x + TURN + item.width(), x + width, y, svg);
+                write!(
+                    svg,
+                    r#"<path d="M {rt} {y} Q {r} {y} {r} {y1} V {b0} Q {r} {b} {rt} {b} H {lt} Q {l} {b} {l} {b0} V {y1} Q {l} {y} {lt} {y}"/>"#,
+                    rt = x + TURN + inner,
+                    y = y,
+                    r = right,
+                    y1 = y + TURN / 2.0,
+                    b0 = back - TURN / 2.0,
+                    b = back,
+                    lt = x + TURN,
+                    l = left
+                )
+                .unwrap();
+                if let Some(label) = label {
+                    draw_label(x + TURN, back + LABEL_HEIGHT - 2.0, label, svg);
+                }
+            }
+            Diagram::Frame { item, label } => {
+                let width = self.width();
+                let top = y - self.up() + SPACING / 2.0;
+                let bottom = y + self.down() - SPACING / 2.0;
+                write!(
+                    svg,
+                    r#"<rect class="group" x="{}" y="{}" width="{}" height="{}" rx="4"/>"#,
+                    x + GAP / 2.0,
+                    top,
+                    width - GAP,
+                    bottom - top
+                )
+                .unwrap();
+                draw_label(x + GAP, top + LABEL_HEIGHT - 2.0, label, svg);
+                line(x, x + GAP, y, svg);
+                item.draw(x + GAP, y, svg);
+                line(x + GAP + item.width(), x + width, y, svg);
+            }
+        }
+    }
+}
+
+fn text_width(text: &str) -> f64 {
+    text.chars().count() as f64 * CHAR_WIDTH
+}
+
+fn line(from: f64, to: f64, y: f64, svg: &mut String) {
+    if to > from {
+        write!(svg, r#"<path d="M {} {} H {}"/>"#, from, y, to).unwrap();
+    }
+}
+
+fn draw_box(x: f64, y: f64, width: f64, text: &str, rounded: bool, svg: &mut String) {
+    write!(
+        svg,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}"/><text x="{}" y="{}">{}</text>"#,
+        x,
+        y - BOX_HEIGHT / 2.0,
+        width,
+        BOX_HEIGHT,
+        if rounded { BOX_HEIGHT / 2.0 } else { 0.0 },
+        x + width / 2.0,
+        y + 4.0,
+        escape_xml(text)
+    )
+    .unwrap();
+}
+
+fn draw_label(x: f64, y: f64, text: &str, svg: &mut String) {
+    write!(svg, r#"<text class="label" x="{}" y="{}">{}</text>"#, x, y, escape_xml(text)).unwrap();
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
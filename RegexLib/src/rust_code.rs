@@ -0,0 +1,228 @@
+use crate::ast::{Anchor, Class, ClassItem, Flags, GroupRef, Node, PerlClass};
+use crate::RegexPart;
+
+// Invocations longer than this are split one argument per line.
+const LINE_WIDTH: usize = 100;
+
+impl RegexPart {
+    /// Prints the combinator macro invocation that builds this part, so a
+    /// parsed pattern can be pasted into code.
+    ///
+    /// Parts without a matching macro, such as `\B` or flags that clear a
+    /// setting other than case insensitivity, are printed as `raw!`.
+    ///
+    /// ```
+    /// use RegexLib::*;
+    ///
+    /// let part = RegexPart::parse(r"([a-z_]\w*)=\d+").unwrap();
+    /// assert_eq!(
+    ///     part.to_rust_code(),
+    ///     r#"sequence!(
+    ///     capturing_group!(sequence!(char_class!['a'..='z', '_'], zero_or_more!(alpha_numeric!()))),
+    ///     escape!("="),
+    ///     one_or_more!(digit!())
+    /// )"#
+    /// );
+    /// ```
+    pub fn to_rust_code(&self) -> String {
+        let mut out = String::new();
+        code(self.node()).print(0, &mut out);
+        out
+    }
+}
+
+enum Code {
+    Atom(String),
+    Call { open: &'static str, args: Vec<Code>, close: &'static str },
+}
+
+impl Code {
+    fn call(name: &'static str, args: Vec<Code>) -> Code {
+        Code::Call { open: name, args, close: ")" }
+    }
+
+    fn flat(&self) -> String {
+        match self {
+            Code::Atom(text) => text.clone(),
+            Code::Call { open, args, close } => {
+                let args: Vec<_> = args.iter().map(Code::flat).collect();
+                format!("{}{}{}", open, args.join(", "), close)
+            }
+        }
+    }
+
+    fn print(&self, indent: usize, out: &mut String) {
+        let flat = self.flat();
+        match self {
+            Code::Call { open, args, close } if indent + flat.len() > LINE_WIDTH => {
+                out.push_str(open);
+                for (i, arg) in args.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    out.push_str(&" ".repeat(indent + 4));
+                    arg.print(indent + 4, out);
+                }
+                out.push('\n');
+                out.push_str(&" ".repeat(indent));
+                out.push_str(close);
+            }
+            _ => out.push_str(&flat),
+        }
+    }
+}
+
+fn code(node: &Node) -> Code {
+    match node {
+        Node::Empty => Code::Atom("nothing!()".to_string()),
+        Node::Literal(text) => Code::Atom(format!("escape!({})", string_literal(text))),
+        Node::Raw(source) => raw(source),
+        Node::Class(class) => class_code(class),
+        Node::Concat(nodes) => Code::call("sequence!(", nodes.iter().map(code).collect()),
+        Node::Alt(nodes) if !nodes.is_empty() => Code::call("any_of!(", nodes.iter().map(code).collect()),
+        Node::Repeat { node: inner, min, max, greedy } => {
+            let inner = code(inner);
+            let count = |count: &u32| Code::Atom(count.to_string());
+            match (min, max, greedy) {
+                (0, None, true) => Code::call("zero_or_more!(", vec![inner]),
+                (0, None, false) => Code::call("zero_or_more_lazy!(", vec![inner]),
+                (1, None, true) => Code::call("one_or_more!(", vec![inner]),
+                (1, None, false) => Code::call("one_or_more_lazy!(", vec![inner]),
+                (0, Some(1), true) => Code::call("optional!(", vec![inner]),
+                (0, Some(1), false) => Code::call("optional_lazy!(", vec![inner]),
+                (min, Some(max), true) if min == max => Code::call("exactly!(", vec![inner, count(min)]),
+                (min, None, true) => Code::call("at_least!(", vec![inner, count(min)]),
+                (min, None, false) => Code::call("at_least_lazy!(", vec![inner, count(min)]),
+                (min, Some(max), true) => Code::call("between!(", vec![inner, count(min), count(max)]),
+                (min, Some(max), false) => Code::call("between_lazy!(", vec![inner, count(min), count(max)]),
+            }
+        }
+        Node::Group { node: inner, capturing: false, .. } => Code::call("non_capturing_group!(", vec![code(inner)]),
+        Node::Group { node: inner, name: None, .. } => Code::call("capturing_group!(", vec![code(inner)]),
+        Node::Group { node: inner, name: Some(name), .. } => {
+            Code::call("named_group!(", vec![Code::Atom(string_literal(name)), code(inner)])
+        }
+        Node::Look { node: inner, ahead, negated } => {
+            let name = match (ahead, negated) {
+                (true, false) => "followed_by!(",
+                (true, true) => "not_followed_by!(",
+                (false, false) => "preceded_by!(",
+                (false, true) => "not_preceded_by!(",
+            };
+            Code::call(name, vec![code(inner)])
+        }
+        Node::Anchor(Anchor::Start) => Code::Atom("begin!()".to_string()),
+        Node::Anchor(Anchor::End) => Code::Atom("end!()".to_string()),
+        Node::Anchor(Anchor::WordBoundary) => Code::Atom("word_boundary!()".to_string()),
+        Node::Backref(GroupRef::Index(index)) => Code::Atom(format!("backref!({})", index)),
+        Node::Backref(GroupRef::Name(name)) => Code::Atom(format!("backref!({})", string_literal(name))),
+        Node::Flags { node: inner, flags } => match flag_macros(flags) {
+            Some(macros) => macros.into_iter().fold(code(inner), |inner, name| Code::call(name, vec![inner])),
+            None => raw(&RegexPart::from(node.clone()).source()),
+        },
+        Node::Alt(_) | Node::Anchor(Anchor::NotWordBoundary) => raw(&RegexPart::from(node.clone()).source()),
+    }
+}
+
+// One macro per flag, or `None` if a flag has no macro.
+fn flag_macros(flags: &Flags) -> Option<Vec<&'static str>> {
+    let settings = [
+        (flags.case_insensitive, Some("case_insensitive!("), Some("case_sensitive!(")),
+        (flags.multi_line, Some("multiline!("), None),
+        (flags.dot_matches_new_line, Some("dot_matches_newline!("), None),
+        (flags.ignore_whitespace, Some("ignore_whitespace!("), None),
+        (flags.swap_greed, Some("swap_greed!("), None),
+        (flags.unicode, None, Some("ascii_only!(")),
+    ];
+    let mut macros = Vec::new();
+    for (flag, on, off) in settings {
+        match flag {
+            Some(true) => macros.push(on?),
+            Some(false) => macros.push(off?),
+            None => {}
+        }
+    }
+    Some(macros)
+}
+
+fn class_code(class: &Class) -> Code {
+    match class {
+        Class::Any => Code::Atom("anything!()".to_string()),
+        Class::Perl { class, negated } => Code::Atom(
+            match (class, negated) {
+                (PerlClass::Digit, false) => "digit!()",
+                (PerlClass::Digit, true) => "non_digit!()",
+                (PerlClass::Word, false) => "alpha_numeric!()",
+                (PerlClass::Word, true) => "non_alpha_numeric!()",
+                (PerlClass::Space, false) => "whitespace!()",
+                (PerlClass::Space, true) => "not_class!(whitespace!())",
+            }
+            .to_string(),
+        ),
+        Class::Unicode { name, negated } => {
+            negate(Code::Atom(format!("unicode_class!({})", string_literal(name))), *negated)
+        }
+        Class::Set { items, negated } => {
+            let mut args = Vec::new();
+            // Runs of single characters are written as one string.
+            let mut chars = String::new();
+            for item in items {
+                if let ClassItem::Char(c) = item {
+                    chars.push(*c);
+                    continue;
+                }
+                push_chars(&mut chars, &mut args);
+                args.push(match item {
+                    ClassItem::Char(_) => unreachable!(),
+                    ClassItem::Range(from, to) => Code::Atom(format!("{:?}..={:?}", from, to)),
+                    ClassItem::Perl { class, negated } => class_code(&Class::Perl { class: *class, negated: *negated }),
+                    ClassItem::Unicode { name, negated } => {
+                        class_code(&Class::Unicode { name: name.clone(), negated: *negated })
+                    }
+                    ClassItem::Class(class) => class_code(class),
+                });
+            }
+            push_chars(&mut chars, &mut args);
+            if args.is_empty() {
+                args.push(Code::Atom("\"\"".to_string()));
+            }
+            negate(Code::Call { open: "char_class![", args, close: "]" }, *negated)
+        }
+        Class::Intersection(left, right) => {
+            Code::call("class_intersection!(", vec![class_code(left), class_code(right)])
+        }
+        Class::Difference(left, right) => {
+            Code::call("class_difference!(", vec![class_code(left), class_code(right)])
+        }
+    }
+}
+
+fn push_chars(chars: &mut String, args: &mut Vec<Code>) {
+    let mut taken = chars.chars();
+    let arg = match (taken.next(), taken.next()) {
+        (None, _) => return,
+        (Some(c), None) => format!("{:?}", c),
+        _ => string_literal(chars),
+    };
+    args.push(Code::Atom(arg));
+    chars.clear();
+}
+
+fn negate(class: Code, negated: bool) -> Code {
+    if negated {
+        Code::call("not_class!(", vec![class])
+    } else {
+        class
+    }
+}
+
+fn raw(source: &str) -> Code {
+    Code::Atom(format!("raw!({})", string_literal(source)))
+}
+
+// Patterns read better as raw strings, which need no doubled backslashes.
+fn string_literal(text: &str) -> String {
+    if text.contains('\\') && !text.contains('"') && !text.chars().any(char::is_control) {
+        format!("r\"{}\"", text)
+    } else {
+        format!("{:?}", text)
+    }
+}
@@ -0,0 +1,102 @@
+use regex::RegexSet;
+
+use crate::compiled::{BuildError, CompiledRegex};
+use crate::RegexPart;
+
+/// Matches many patterns against a haystack at once and tells which of
+/// them match, for example to route input by a table of patterns.
+///
+/// Patterns run together on one `regex::RegexSet`, except those that
+/// need the `fancy` engine, which are tried one by one. Those count as not
+/// matching where the engine gives up, as with `CompiledRegex::is_match`.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// let routes = RegexPartSet::labeled([
+///     ("user", sequence!(begin!(), escape!("/users/"), one_or_more!(digit!()), end!())),
+///     ("static", sequence!(begin!(), escape!("/static/"))),
+///     ("any", sequence!(begin!(), escape!("/"))),
+/// ])
+/// .unwrap();
+/// assert_eq!(routes.matches("/users/42").collect::<Vec<_>>(), [0, 2]);
+/// assert_eq!(routes.matching_labels("/static/app.css").collect::<Vec<_>>(), ["static", "any"]);
+/// assert_eq!(routes.label(1), Some("static"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegexPartSet {
+    set: RegexSet,
+    // Index in the whole set of every pattern in `set`.
+    indices: Vec<usize>,
+    fancy: Vec<(usize, CompiledRegex)>,
+    labels: Vec<Option<String>>,
+}
+
+impl RegexPartSet {
+    pub fn new<I: IntoIterator<Item = RegexPart>>(parts: I) -> Result<Self, BuildError> {
+        RegexPartSet::build(parts.into_iter().map(|part| (None, part)))
+    }
+
+    /// Like `new`, with a label for each pattern that `label` and
+    /// `matching_labels` report.
+    pub fn labeled<I, L>(entries: I) -> Result<Self, BuildError>
+    where
+        I: IntoIterator<Item = (L, RegexPart)>,
+        L: Into<String>,
+    {
+        RegexPartSet::build(entries.into_iter().map(|(label, part)| (Some(label.into()), part)))
+    }
+
+    fn build(entries: impl Iterator<Item = (Option<String>, RegexPart)>) -> Result<Self, BuildError> {
+        let mut sources = Vec::new();
+        let mut indices = Vec::new();
+        let mut fancy = Vec::new();
+        let mut labels = Vec::new();
+        for (index, (label, part)) in entries.enumerate() {
+            if part.node().fancy_construct().is_some() {
+                fancy.push((index, part.compile()?));
+            } else {
+                sources.push(part.source());
+                indices.push(index);
+            }
+            labels.push(label);
+        }
+        Ok(RegexPartSet { set: RegexSet::new(sources)?, indices, fancy, labels })
+    }
+
+    /// Number of patterns in the set.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// The label of pattern `index`, if it was given one.
+    pub fn label(&self, index: usize) -> Option<&str> {
+        self.labels.get(index)?.as_deref()
+    }
+
+    /// Whether any pattern matches.
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.set.is_match(haystack) || self.fancy.iter().any(|(_, regex)| regex.is_match(haystack))
+    }
+
+    /// Indices of the patterns that match somewhere in `haystack`, in
+    /// ascending order.
+    pub fn matches(&self, haystack: &str) -> impl Iterator<Item = usize> {
+        let mut matched: Vec<usize> = self.set.matches(haystack).into_iter().map(|index| self.indices[index]).collect();
+        if !self.fancy.is_empty() {
+            matched.extend(self.fancy.iter().filter(|(_, regex)| regex.is_match(haystack)).map(|(index, _)| *index));
+            matched.sort_unstable();
+        }
+        matched.into_iter()
+    }
+
+    /// Labels of the matching patterns, in pattern order. Patterns without
+    /// a label are left out.
+    pub fn matching_labels<'s>(&'s self, haystack: &str) -> impl Iterator<Item = &'s str> {
+        self.matches(haystack).filter_map(move |index| self.label(index))
+    }
+}
@@ -0,0 +1,1192 @@
+use std::ops::Bound;
+
+use crate::*;
+
+#[test]
+fn compile_and_match() {
+    let number = one_or_more!(digit!());
+    let compiled = number.compile().unwrap();
+    assert_eq!(compiled.as_str(), number.source());
+    assert!(compiled.is_match("abc 123"));
+    assert!(!compiled.is_match("abc"));
+
+    let found = compiled.find("abc 123 45").unwrap();
+    assert_eq!(found.as_str(), "123");
+    assert_eq!(found.range(), 4..7);
+
+    let all: Vec<_> = compiled.find_iter("1 22 333").map(|m| m.as_str()).collect();
+    assert_eq!(all, ["1", "22", "333"]);
+
+    assert!(compiled.try_is_match("7").unwrap());
+    assert_eq!(compiled.try_find("a 12").unwrap().map(|m| m.range()), Some(2..4));
+    assert!(compiled.try_captures("abc").unwrap().is_none());
+}
+
+#[test]
+fn compile_error() {
+    let broken = raw!("(unclosed");
+    assert!(matches!(broken.compile(), Err(BuildError::Regex(_))));
+}
+
+#[test]
+fn captures() {
+    let pair = sequence!(
+        capturing_group!(one_or_more!(alpha_numeric!())),
+        raw!("="),
+        capturing_group!(one_or_more!(digit!()))
+    );
+    let compiled = pair.compile().unwrap();
+    let captures = compiled.captures("set width=42;").unwrap();
+    assert_eq!(captures.len(), 3);
+    assert_eq!(&captures[0], "width=42");
+    assert_eq!(&captures[1], "width");
+    assert_eq!(captures.get(2).unwrap().range(), 10..12);
+    assert!(captures.get(3).is_none());
+
+    let keys: Vec<_> = compiled
+        .captures_iter("a=1 b=2")
+        .map(|captures| captures[1].to_string())
+        .collect();
+    assert_eq!(keys, ["a", "b"]);
+}
+
+#[test]
+fn replace_and_split() {
+    let spaces = one_or_more!(whitespace!()).compile().unwrap();
+    assert_eq!(spaces.replace_all("a  b \t c", " "), "a b c");
+    assert_eq!(spaces.split(" a  b c").collect::<Vec<_>>(), ["", "a", "b", "c"]);
+    assert_eq!(spaces.split("").collect::<Vec<_>>(), [""]);
+
+    let number = capturing_group!(one_or_more!(digit!())).compile().unwrap();
+    assert_eq!(number.replace_all("x1 y22", "<$1>"), "x<1> y<22>");
+    let doubled = number.replace_all_with("x1 y22", |captures| {
+        (captures[1].parse::<u32>().unwrap() * 2).to_string()
+    });
+    assert_eq!(doubled, "x2 y44");
+    assert!(matches!(number.replace_all_with("none", |_| String::new()), std::borrow::Cow::Borrowed("none")));
+}
+
+static_regex!(static ref WORD = one_or_more!(alpha_numeric!()););
+static_regex!(pub(crate) static ref SPACES = one_or_more!(whitespace!()););
+
+#[test]
+fn static_regex() {
+    assert_eq!(WORD.find("  hello ").unwrap().as_str(), "hello");
+    assert_eq!(SPACES.split("a b").count(), 2);
+
+    fn digits() -> &'static CompiledRegex {
+        static_regex!(one_or_more!(digit!()))
+    }
+    assert!(std::ptr::eq(digits(), digits()));
+    assert!(digits().is_match("7"));
+}
+
+#[test]
+fn escape_metacharacters() {
+    assert_eq!(escape!("a.b").source(), r"a\.b");
+    assert_eq!(escape("1+1=2?"), r"1\+1=2\?");
+    assert_eq!(escape(r"\d[x]{2}^$|()*"), r"\\d\[x\]\{2\}\^\$\|\(\)\*");
+    assert_eq!(escape("#&-~"), r"\#\&\-\~");
+    assert_eq!(escape("a b\tc\n"), r"a\ b\tc\n");
+    assert_eq!(escape("<word>/'\"_"), "<word>/'\"_");
+    assert_eq!(escape("non\u{A0}breaking\u{3000}"), r"non\x{A0}breaking\x{3000}");
+    assert_eq!(escape("привет, мир"), r"привет,\ мир");
+
+    let owned = String::from("x.y");
+    assert_eq!(escape!(owned).source(), r"x\.y");
+}
+
+#[test]
+fn escape_const() {
+    const ESCAPED: &str = escape_const!("(a.b)* ё\u{2028}");
+    assert_eq!(ESCAPED, escape("(a.b)* ё\u{2028}"));
+    assert_eq!(escape_const!(""), "");
+}
+
+proptest::proptest! {
+    #[test]
+    fn escape_matches_exactly_itself(text in "\\PC*|[\\s\\\\.+*?()|\\[\\]{}^$#&~-]*") {
+        let exact = raw!(&format!("^{}$", escape!(text).source())).compile().unwrap();
+        proptest::prop_assert!(exact.is_match(&text));
+
+        let verbose = raw!(&format!("(?x)^{}$", escape(&text))).compile().unwrap();
+        proptest::prop_assert!(verbose.is_match(&text));
+
+        let found = escape!(text).compile().unwrap();
+        proptest::prop_assert_eq!(found.find(&text).map(|m| m.as_str()), Some(text.as_str()));
+    }
+
+    #[test]
+    fn escape_rejects_other_strings(text in "\\PC{0,8}", other in "\\PC{0,8}") {
+        proptest::prop_assume!(text != other);
+        let exact = raw!(&format!("^{}$", escape(&text))).compile().unwrap();
+        proptest::prop_assert!(!exact.is_match(&other));
+    }
+}
+
+#[test]
+fn minimal_parentheses() {
+    assert_eq!(one_or_more!(digit!()).source(), r"\d+");
+    assert_eq!(one_or_more!(escape!("ab")).source(), "(?:ab)+");
+    assert_eq!(one_or_more!(raw!("test")).source(), "(?:test)+");
+    assert_eq!(sequence!(any_of!(escape!("a"), escape!("b")), escape!("c")).source(), "(?:a|b)c");
+    assert_eq!(any_of!(sequence!(escape!("a"), escape!("b")), escape!("c")).source(), "ab|c");
+    assert_eq!(optional!(one_or_more!(digit!())).source(), r"(?:\d+)?");
+    assert_eq!(lazy!(anything!()).source(), ".*?");
+    assert_eq!(capturing_group!(any_of!(digit!(), whitespace!())).source(), r"(\d|\s)");
+    assert_eq!(non_capturing_group!(escape!("a")).source(), "(?:a)");
+    assert_eq!(one_or_more!(sequence!(begin!())).source(), "(?:^)+");
+    assert_eq!(not!(escape!("\\")).source(), r"(?!\\)");
+    assert_eq!(sequence!(nothing!(), word_boundary!(), non_digit!(), end!()).source(), r"\b\D$");
+}
+
+#[test]
+fn class_rendering() {
+    assert_eq!(cyrillic_letter!().source(), "[а-яА-ЯёЁ]");
+    assert_eq!(punctuation!().source(), "[.,!?;:]");
+
+    let special = RegexPart::from(Node::Class(ast::Class::Set {
+        items: vec![ast::ClassItem::Char(']'), ast::ClassItem::Char('-'), ast::ClassItem::Range('a', 'c')],
+        negated: true,
+    }));
+    assert_eq!(special.source(), r"[^\]\-a-c]");
+    assert!(special.compile().unwrap().is_match("-d"));
+}
+
+#[test]
+fn inspect_tree() {
+    let part = sequence!(escape!("v"), one_or_more!(digit!()));
+    let Node::Concat(nodes) = part.node() else { panic!("expected concatenation") };
+    assert_eq!(nodes[0], Node::Literal("v".to_string()));
+    assert!(matches!(nodes[1], Node::Repeat { min: 1, max: None, greedy: true, .. }));
+    assert_eq!(part.to_string(), part.source());
+    assert_eq!(RegexPart::from(part.clone().into_node()), part);
+}
+
+#[test]
+fn named_groups() {
+    let year = named_group!("year", one_or_more!(digit!()));
+    assert_eq!(year.source(), r"(?P<year>\d+)");
+    let captures = year.compile().unwrap().captures("in 2024").unwrap();
+    assert_eq!(&captures["year"], "2024");
+    assert!(captures.name("month").is_none());
+}
+
+regex_struct! {
+    #[derive(Debug, PartialEq)]
+    struct LogLine {
+        level: String,
+        code: u16,
+        message: String,
+    } = sequence!(
+        escape!("["),
+        named_group!("level", one_or_more!(alpha_numeric!())),
+        escape!("] "),
+        named_group!("code", one_or_more!(digit!())),
+        escape!(": "),
+        named_group!("message", zero_or_more!(anything!()))
+    );
+}
+
+regex_struct! {
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Choice {
+        left: u8,
+        right: u8,
+    } = any_of!(named_group!("left", digit!()), named_group!("right", digit!()));
+}
+
+regex_struct! {
+    #[derive(Debug, PartialEq)]
+    struct Address {
+        host: String,
+        port: Option<u16>,
+    } = sequence!(
+        named_group!("host", one_or_more!(alpha_numeric!())),
+        optional!(sequence!(escape!(":"), named_group!("port", one_or_more!(digit!()))))
+    );
+}
+
+#[test]
+fn regex_struct() {
+    assert_eq!(
+        LogLine::parse("[warn] 404: not found"),
+        Ok(LogLine { level: "warn".to_string(), code: 404, message: "not found".to_string() })
+    );
+    assert_eq!(LogLine::parse("[warn] 404: x\nextra"), Err(MatchError::NoMatch));
+    assert_eq!(LogLine::parse("prefix [warn] 1: x"), Err(MatchError::NoMatch));
+
+    let error = "[warn] 99999: too big".parse::<LogLine>().unwrap_err();
+    let MatchError::Parse { field, value, .. } = &error else { panic!("expected parse error") };
+    assert_eq!((*field, value.as_str()), ("code", "99999"));
+    assert!(error.to_string().contains("field `code`"));
+
+    assert!(std::ptr::eq(LogLine::regex(), LogLine::regex()));
+    assert_eq!(Choice::parse("7").unwrap_err(), MatchError::MissingGroup("right"));
+
+    assert_eq!(Address::parse("localhost"), Ok(Address { host: "localhost".to_string(), port: None }));
+    assert_eq!(Address::parse("db:5432"), Ok(Address { host: "db".to_string(), port: Some(5432) }));
+    let MatchError::Parse { field, .. } = Address::parse("db:99999").unwrap_err() else { panic!("expected parse error") };
+    assert_eq!(field, "port");
+}
+
+lexer! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Token {
+        If => escape!("if"),
+        Ident => one_or_more!(alpha_numeric!()),
+        Assign => escape!("="),
+        Equals => escape!("=="),
+        Comment => skip sequence!(escape!("#"), zero_or_more!(anything!())),
+        Ws => skip one_or_more!(whitespace!())
+    }
+}
+
+#[test]
+fn lexer() {
+    let tokens: Vec<_> = Token::lex("if iffy == x").collect();
+    assert_eq!(
+        tokens,
+        [
+            (Token::If, 0..2, "if"),
+            (Token::Ident, 3..7, "iffy"),
+            (Token::Equals, 8..10, "=="),
+            (Token::Ident, 11..12, "x")
+        ]
+    );
+
+    let tokens: Vec<_> = Token::lex("a = €$ b ?# c ==\n!").map(|(token, _, text)| (token, text)).collect();
+    assert_eq!(
+        tokens,
+        [
+            (Token::Ident, "a"),
+            (Token::Assign, "="),
+            (Token::Error, "€$"),
+            (Token::Ident, "b"),
+            (Token::Error, "?"),
+            (Token::Error, "!")
+        ]
+    );
+    assert_eq!(Token::lex("").count(), 0);
+    assert_eq!(Token::lex(" # only a comment").count(), 0);
+    assert!(std::ptr::eq(Token::lexer(), Token::lexer()));
+
+    let lines = Lexer::new([(Some(1), optional!(digit!())), (Some(2), escape!("x"))], 0).unwrap();
+    let tokens: Vec<_> = lines.tokens("x-7").map(|(token, range, _)| (token, range)).collect();
+    assert_eq!(tokens, [(2, 0..1), (0, 1..2), (1, 2..3)]);
+    assert!(Lexer::new([(Some(1), raw!("("))], 0).is_err());
+
+    let short_first = Lexer::new([(Some(1), any_of!(escape!("a"), escape!("ab")))], 0).unwrap();
+    let tokens: Vec<_> = short_first.tokens("ab").map(|(token, _, text)| (token, text)).collect();
+    assert_eq!(tokens, [(1, "a"), (0, "b")]);
+    let split = Lexer::new([(Some(1), escape!("a")), (Some(2), escape!("ab"))], 0).unwrap();
+    let tokens: Vec<_> = split.tokens("ab").map(|(token, _, text)| (token, text)).collect();
+    assert_eq!(tokens, [(2, "ab")]);
+}
+
+#[test]
+fn regex_part_set() {
+    let set = RegexPartSet::new([digit!(), escape!("a"), sequence!(begin!(), escape!("x"))]).unwrap();
+    assert_eq!((set.len(), set.label(0)), (3, None));
+    assert_eq!(set.matches("1a").collect::<Vec<_>>(), [0, 1]);
+    assert_eq!(set.matches("x").collect::<Vec<_>>(), [2]);
+    assert_eq!(set.matching_labels("x1").count(), 0);
+    assert!(set.is_match("x") && !set.is_match("b"));
+
+    let empty = RegexPartSet::new([]).unwrap();
+    assert!(empty.is_empty() && !empty.is_match("") && empty.label(0).is_none());
+    assert!(matches!(RegexPartSet::new([digit!(), raw!("(")]), Err(BuildError::Regex(_))));
+    #[cfg(not(feature = "fancy"))]
+    assert!(matches!(RegexPartSet::new([followed_by!(digit!())]), Err(BuildError::Unsupported("lookaround"))));
+}
+
+// Compares `regex_const!` with the runtime macro of the same name and
+// records the name as covered.
+macro_rules! assert_const_matches_runtime {
+    ($covered:ident, $name:literal, $($part:tt)*) => {
+        assert_eq!(regex_const! { $($part)* }.as_str(), ($($part)*).source(), "`{}!`", $name);
+        $covered.push($name);
+    };
+}
+
+#[test]
+fn regex_const_renders_like_runtime() {
+    let mut covered = Vec::new();
+    assert_const_matches_runtime!(covered, "raw", sequence!(raw!("c|d"), raw!(r#"raw "quoted""#)));
+    assert_const_matches_runtime!(covered, "escape", escape!("a.b ё\t\u{A0}\"\\"));
+    assert_const_matches_runtime!(covered, "non_capturing_group", non_capturing_group!(non_digit!()));
+    assert_const_matches_runtime!(covered, "capturing_group", capturing_group!(anything!()));
+    assert_const_matches_runtime!(covered, "named_group", named_group!("word", alpha_numeric!()));
+    assert_const_matches_runtime!(covered, "one_or_more", RegexLib::one_or_more!(crate::digit!()));
+    assert_const_matches_runtime!(covered, "zero_or_more", zero_or_more!(escape!("ab")));
+    assert_const_matches_runtime!(covered, "optional", optional!(whitespace!()));
+    assert_const_matches_runtime!(covered, "lazy", lazy!(anything!()));
+    assert_const_matches_runtime!(covered, "one_or_more_lazy", one_or_more_lazy!(digit!()));
+    assert_const_matches_runtime!(covered, "zero_or_more_lazy", zero_or_more_lazy!(digit!()));
+    assert_const_matches_runtime!(covered, "optional_lazy", optional_lazy!(digit!()));
+    assert_const_matches_runtime!(covered, "times", sequence!(times!(digit!(), 2), times!(digit!(), 1, 3)));
+    assert_const_matches_runtime!(covered, "exactly", exactly!(digit!(), 4));
+    assert_const_matches_runtime!(covered, "at_least", any_of!(at_least!(digit!(), 0), at_least!(digit!(), 2)));
+    assert_const_matches_runtime!(covered, "at_least_lazy", at_least_lazy!(digit!(), 5));
+    assert_const_matches_runtime!(covered, "between", any_of!(between!(digit!(), 0, 1), between!(digit!(), 2, 2)));
+    assert_const_matches_runtime!(covered, "between_lazy", between_lazy!(digit!(), 1, 2));
+    assert_const_matches_runtime!(covered, "any_of", any_of!(escape!("a"), sequence!(digit!(), digit!())));
+    assert_const_matches_runtime!(covered, "sequence", sequence!(escape!("a"), any_of!(digit!(), whitespace!())));
+    assert_const_matches_runtime!(covered, "custom_word_boundary", custom_word_boundary!());
+    assert_const_matches_runtime!(covered, "anything", anything!());
+    assert_const_matches_runtime!(covered, "nothing", sequence!(nothing!(), digit!()));
+    assert_const_matches_runtime!(covered, "word_boundary", word_boundary!());
+    assert_const_matches_runtime!(covered, "begin", begin!());
+    assert_const_matches_runtime!(covered, "end", end!());
+    assert_const_matches_runtime!(covered, "whitespace", whitespace!());
+    assert_const_matches_runtime!(covered, "alpha_numeric", alpha_numeric!());
+    assert_const_matches_runtime!(covered, "non_alpha_numeric", non_alpha_numeric!());
+    assert_const_matches_runtime!(covered, "digit", digit!());
+    assert_const_matches_runtime!(covered, "non_digit", non_digit!());
+    assert_const_matches_runtime!(covered, "cyrillic_letter", cyrillic_letter!());
+    assert_const_matches_runtime!(covered, "punctuation", punctuation!());
+    assert_const_matches_runtime!(covered, "marks", marks!());
+    assert_const_matches_runtime!(covered, "char_class", char_class!['a'..='z', '_', "-]", '\'', digit!(), escape!(".")]);
+    assert_const_matches_runtime!(covered, "not_class", not_class!(char_class![cyrillic_letter!(), 'x']));
+    assert_const_matches_runtime!(covered, "class_intersection", class_intersection!(alpha_numeric!(), 'a'));
+    assert_const_matches_runtime!(covered, "class_difference", class_difference!(alpha_numeric!(), digit!()));
+    assert_const_matches_runtime!(covered, "unicode_class", unicode_class!("Greek"));
+    assert_const_matches_runtime!(covered, "case_insensitive", case_insensitive!(escape!("a")));
+    assert_const_matches_runtime!(covered, "case_sensitive", case_insensitive!(case_sensitive!(escape!("a"))));
+    assert_const_matches_runtime!(covered, "multiline", multiline!(end!()));
+    assert_const_matches_runtime!(covered, "dot_matches_newline", dot_matches_newline!(anything!()));
+    assert_const_matches_runtime!(covered, "ignore_whitespace", ignore_whitespace!(raw!("a b")));
+    assert_const_matches_runtime!(covered, "swap_greed", swap_greed!(zero_or_more!(digit!())));
+    assert_const_matches_runtime!(covered, "ascii_only", ascii_only!(alpha_numeric!()));
+    #[cfg(feature = "fancy")]
+    {
+        assert_const_matches_runtime!(covered, "not", not!(digit!()));
+        assert_const_matches_runtime!(covered, "not_followed_by", not_followed_by!(digit!()));
+        assert_const_matches_runtime!(covered, "followed_by", followed_by!(digit!()));
+        assert_const_matches_runtime!(covered, "preceded_by", preceded_by!(whitespace!()));
+        assert_const_matches_runtime!(covered, "not_preceded_by", not_preceded_by!(digit!()));
+        assert_const_matches_runtime!(covered, "not_escaped", not_escaped!(escape!("*")));
+        assert_const_matches_runtime!(covered, "backref", sequence!(capturing_group!(digit!()), backref!(1)));
+        assert_const_matches_runtime!(covered, "backref", sequence!(named_group!("q", escape!("'")), backref!("q")));
+
+        // Both sides build from the same table, so each entry needs one comparison.
+        for (name, _) in combinators::COMBINATORS {
+            assert!(covered.contains(name), "`{}!` is not compared with `regex_const!`", name);
+        }
+    }
+}
+
+#[test]
+fn char_class_algebra() {
+    let identifier = char_class!['a'..='z', 'A'..='Z', '_', digit!()];
+    assert_eq!(identifier.source(), r"[a-zA-Z_\d]");
+    assert_eq!(char_class![digit!()].source(), r"\d");
+    assert_eq!(char_class!["-^]", ' '].source(), r"[\-\^\]\ ]");
+    assert_eq!(not_class!(digit!()).source(), r"\D");
+    assert_eq!(not_class!(unicode_class!("Greek")).source(), r"\P{Greek}");
+    assert_eq!(not_class!(anything!()).source(), r"[^\s\S]");
+    assert_eq!(char_class![anything!(), 'a'].source(), r"[\s\Sa]");
+    assert_eq!(class_difference!(anything!(), digit!()).source(), r"[[\s\S]--\d]");
+    for part in [char_class![anything!(), 'a'], dot_matches_newline!(char_class![anything!(), 'a'])] {
+        assert!(part.compile().unwrap().is_match("\n"), "{}", part);
+    }
+    for part in [not_class!(anything!()), dot_matches_newline!(not_class!(anything!()))] {
+        assert!(!part.compile().unwrap().is_match("\n"), "{}", part);
+    }
+
+    let consonant = class_difference!(char_class!['a'..='z'], "aeiou");
+    assert_eq!(consonant.source(), "[[a-z]--[aeiou]]");
+    let regex = one_or_more!(consonant).compile().unwrap();
+    assert_eq!(regex.find_iter("strength").map(|m| m.as_str()).collect::<Vec<_>>(), ["str", "ngth"]);
+
+    let greek = class_intersection!(unicode_class!("Greek"), unicode_class!("L")).compile().unwrap();
+    assert!(greek.is_match("λ"));
+    assert!(!greek.is_match("l"));
+    let not_greek = not_class!(class_intersection!(unicode_class!("Greek"), unicode_class!("L")));
+    assert_eq!(not_greek.source(), r"[^[\p{Greek}&&\p{L}]]");
+    assert!(not_greek.compile().unwrap().is_match("l"));
+    assert!(!not_greek.compile().unwrap().is_match("λ"));
+
+    assert_eq!(char_class![""].source(), r"[^\s\S]");
+    assert_eq!(not_class!(char_class![""]).source(), r"[\s\S]");
+}
+
+#[test]
+#[should_panic(expected = "is not a character class")]
+fn char_class_rejects_sequences() {
+    let _ = char_class![escape!("ab")];
+}
+
+#[test]
+fn quantifiers() {
+    let d = digit!();
+    assert_eq!(times!(d, 2).source(), r"\d{2,}");
+    assert_eq!(times!(d, 2, 5).source(), r"\d{2,5}");
+    assert_eq!(exactly!(d, 3).source(), r"\d{3}");
+    assert_eq!(at_least!(d, 1).source(), r"\d+");
+    assert_eq!(between!(d, 0, 1).source(), r"\d?");
+    assert_eq!(between!(escape!("ab"), 1, 2).source(), "(?:ab){1,2}");
+    assert_eq!(one_or_more_lazy!(d).source(), r"\d+?");
+    assert_eq!(zero_or_more_lazy!(d).source(), r"\d*?");
+    assert_eq!(optional_lazy!(d).source(), r"\d??");
+    assert_eq!(at_least_lazy!(d, 2).source(), r"\d{2,}?");
+    assert_eq!(between_lazy!(d, 2, 3).source(), r"\d{2,3}?");
+
+    let year = sequence!(word_boundary!(), exactly!(d, 4), word_boundary!()).compile().unwrap();
+    assert_eq!(year.find("in 123 or 2024").unwrap().as_str(), "2024");
+    let tag = sequence!(escape!("<"), one_or_more_lazy!(anything!()), escape!(">")).compile().unwrap();
+    assert_eq!(tag.find("<a><b>").unwrap().as_str(), "<a>");
+}
+
+#[test]
+#[should_panic(expected = "repetition minimum 3 exceeds its maximum 2")]
+fn between_rejects_inverted_bounds() {
+    let _ = between!(digit!(), 3, 2);
+}
+
+#[test]
+fn marks() {
+    let marks = one_or_more!(marks!()).compile().unwrap();
+    for mark in MARKS.chars() {
+        assert!(marks.is_match(&mark.to_string()), "{:?}", mark);
+    }
+    assert_eq!(marks.find("«Да», — сказал он…").unwrap().as_str(), "«");
+    assert!(!marks.is_match("слово word 42"));
+}
+
+#[test]
+fn custom_word_boundary() {
+    let word = |text: &str| {
+        sequence!(custom_word_boundary!(), capturing_group!(escape!(text)), custom_word_boundary!())
+            .compile()
+            .unwrap()
+    };
+    let cat = word("кот");
+    for text in ["кот", "Вот кот.", "«кот»", "—кот—", "кот, и пёс", "Это „кот“!"] {
+        assert_eq!(cat.captures(text).map(|c| c[1].to_string()).as_deref(), Some("кот"), "{}", text);
+    }
+    for text in ["котёнок", "скот", "кот42", "котик"] {
+        assert!(!cat.is_match(text), "{}", text);
+    }
+
+    let dont = word("don");
+    assert!(dont.is_match("I don’t know"));
+    assert!(dont.is_match("‘don’"));
+    assert!(!dont.is_match("London"));
+    let words: Vec<_> = one_or_more!(non_alpha_numeric!())
+        .compile()
+        .unwrap()
+        .split("Hello, world — «привет», мир…")
+        .filter(|piece| !piece.is_empty())
+        .collect();
+    assert_eq!(words, ["Hello", "world", "привет", "мир"]);
+}
+
+fn assert_whole_matches(part: RegexPart, valid: &[&str], invalid: &[&str]) {
+    let regex = sequence!(begin!(), part, end!()).compile().unwrap();
+    for text in valid {
+        assert!(regex.is_match(text), "{} should match {}", regex, text);
+    }
+    for text in invalid {
+        assert!(!regex.is_match(text), "{} should not match {}", regex, text);
+    }
+}
+
+#[test]
+fn common_patterns() {
+    assert_whole_matches(patterns::integer(), &["0", "42", "-7", "+13"], &["", "4.2", "--1", "١٢"]);
+    assert_whole_matches(
+        patterns::float(),
+        &["3.14", "-0.5", ".5", "5.", "1e10", "-1.5E-3", "6.02e+23"],
+        &["42", ".", "e5", "1e", "1.2.3"],
+    );
+    assert_whole_matches(
+        patterns::iso_date(),
+        &["2024-02-29", "1999-12-31", "0001-01-01"],
+        &["2024-13-01", "2024-00-10", "2024-01-32", "24-01-01", "2024/01/01"],
+    );
+    assert_whole_matches(
+        patterns::iso_time(),
+        &["00:00", "23:59:59", "13:37:00.123", "08:15:60Z", "12:00+03:00", "12:00-0530"],
+        &["24:00", "12:60", "1:00", "12:00:00+3"],
+    );
+    assert_whole_matches(patterns::iso_datetime(), &["2024-02-29T13:37:00Z"], &["2024-02-29 13:37"]);
+    assert_whole_matches(
+        patterns::ipv4(),
+        &["0.0.0.0", "127.0.0.1", "192.168.1.254", "255.255.255.255"],
+        &["256.1.1.1", "1.2.3", "01.2.3.4", "1.2.3.4.5"],
+    );
+    assert_whole_matches(
+        patterns::ipv6(),
+        &[
+            "2001:0db8:85a3:0000:0000:8a2e:0370:7334",
+            "2001:db8::8a2e:370:7334",
+            "::1",
+            "::",
+            "fe80::",
+            "1::8",
+            "1:2:3:4:5:6:7::",
+            "::ffff:192.0.2.128",
+            "64:ff9b::192.0.2.33",
+            "1:2:3:4:5:6:1.2.3.4",
+        ],
+        &["1:2:3:4:5:6:7:8:9", "1::2::3", "12345::", ":1:2", "g::1", "1:2:3:4:5:6:7:1.2.3.4"],
+    );
+    assert_whole_matches(
+        patterns::email(),
+        &["user@example.com", "first.last+tag@mail.co.uk", "o'brien@sub-domain.example.org"],
+        &["user@localhost", "user@@example.com", ".user@example.com", "user@-example.com", "user@example.c"],
+    );
+    assert_whole_matches(
+        patterns::url(),
+        &[
+            "https://example.com",
+            "http://localhost:8080/api/v1?query=1&b=2#top",
+            "ftp://192.168.0.1/file.txt",
+            "https://[2001:db8::1]:443/",
+        ],
+        &["example.com", "mailto:user@example.com", "https://exa mple.com", "https:/example.com"],
+    );
+    assert_whole_matches(
+        patterns::uuid(),
+        &["123e4567-e89b-12d3-a456-426614174000", "00000000-0000-0000-0000-000000000000"],
+        &["123e4567e89b12d3a456426614174000", "123e4567-e89b-12d3-a456-42661417400g"],
+    );
+    assert_whole_matches(
+        patterns::semver(),
+        &["0.1.0", "1.2.3-alpha.1", "1.0.0-0.3.7", "1.0.0-x-y-z.--", "1.0.0+20130313144700", "1.0.0-beta+exp.sha.5114f85"],
+        &["1.2", "01.2.3", "1.2.3-01", "1.2.3-", "1.2.3+", "v1.2.3"],
+    );
+    assert_whole_matches(
+        patterns::quoted_string('"'),
+        &[r#""""#, r#""plain""#, r#""say \"hi\"""#, r#""back\\slash""#],
+        &[r#""open"#, r#""a"b""#, r#""trailing\""#],
+    );
+    assert_whole_matches(patterns::quoted_string('\''), &["'it\\'s'"], &["'it's'"]);
+
+    let assignment = sequence!(
+        named_group!("name", one_or_more!(alpha_numeric!())),
+        escape!(" = "),
+        named_group!("value", any_of!(patterns::float(), patterns::integer(), patterns::quoted_string('"')))
+    )
+    .compile()
+    .unwrap();
+    let values: Vec<_> = assignment
+        .captures_iter(r#"x = 1.5e3, y = -2, s = "a \"b\"""#)
+        .map(|c| c["value"].to_string())
+        .collect();
+    assert_eq!(values, ["1.5e3", "-2", r#""a \"b\"""#]);
+}
+
+#[test]
+fn operators() {
+    let word = alpha_numeric!().many1();
+    let number = digit!().many1();
+    assert_eq!((&word + escape!(".") + &number).source(), r"\w+\.\d+");
+    assert_eq!((&word | &number | escape!("-")).source(), r"\w+|\d+|\-");
+    assert_eq!((escape!("a") + (escape!("b") | escape!("c"))).source(), "a(?:b|c)");
+    assert_eq!(((escape!("a") | escape!("b")) + escape!("c")).source(), "(?:a|b)c");
+    assert_eq!((escape!("ab") + escape!("cd")).many().source(), "(?:abcd)*");
+
+    let concat = escape!("a") + escape!("b") + escape!("c");
+    assert!(matches!(concat.node(), Node::Concat(nodes) if nodes.len() == 3));
+    let alternation = escape!("a") | escape!("b") | escape!("c");
+    assert!(matches!(alternation.node(), Node::Alt(nodes) if nodes.len() == 3));
+
+    assert_eq!(digit!().opt().source(), r"\d?");
+    assert_eq!(digit!().repeat(2..=4).source(), r"\d{2,4}");
+    assert_eq!(digit!().repeat(2..5).source(), r"\d{2,4}");
+    assert_eq!(digit!().repeat(3..).source(), r"\d{3,}");
+    assert_eq!(digit!().repeat(..=2).source(), r"\d{0,2}");
+    assert_eq!(digit!().repeat(..).source(), r"\d*");
+    assert_eq!(digit!().repeat((Bound::Excluded(2), Bound::Included(4))).source(), r"\d{3,4}");
+    assert_eq!(digit!().many().lazy().source(), r"\d*?");
+    assert_eq!(word.clone().capture().source(), r"(\w+)");
+    assert_eq!(word.clone().named("w").source(), r"(?P<w>\w+)");
+
+    let pair = word.clone().named("key") + escape!("=") + (number.named("value") | escape!("null"));
+    let pairs = pair.compile().unwrap();
+    let found: Vec<_> = pairs.captures_iter("a=1 b=null").map(|c| c["key"].to_string()).collect();
+    assert_eq!(found, ["a", "b"]);
+
+    // The macros stay equivalent to the methods.
+    assert_eq!(one_or_more!(word), word.clone().many1());
+    assert_eq!(sequence!(word, digit!()), &word + digit!());
+    assert_eq!(between_lazy!(word, 1, 2), word.repeat(1..=2).lazy());
+}
+
+#[test]
+fn lazy_without_repetition() {
+    assert_eq!(digit!().lazy(), digit!());
+    assert_eq!(digit!().capture().lazy().source(), r"(\d)");
+}
+
+#[test]
+#[should_panic(expected = "empty repetition range")]
+fn repeat_rejects_range_after_max() {
+    let _ = digit!().repeat((Bound::Excluded(u32::MAX), Bound::Unbounded));
+}
+
+#[test]
+fn optimize() {
+    let optimized = |part: RegexPart| part.optimize().source();
+    assert_eq!(optimized(any_of!(escape!("foo"), escape!("fob"))), "fo[ob]");
+    let dot_or_a = dot_matches_newline!(any_of!(anything!(), escape!("a")));
+    assert_eq!(optimized(dot_or_a.clone()), "(?s:.|a)");
+    assert!(dot_or_a.optimize().compile().unwrap().is_match("\n"));
+    assert!(!any_of!(anything!(), escape!("a")).optimize().compile().unwrap().is_match("\n"));
+    assert_eq!(optimized(one_or_more!(non_capturing_group!(non_capturing_group!(escape!("a"))))), "a+");
+    assert_eq!(optimized(sequence!(escape!("a"), sequence!(escape!("b"), nothing!()), escape!("c"))), "abc");
+    assert_eq!(optimized(any_of!(escape!("a"), escape!("b"), digit!(), escape!("xy"))), r"[ab\d]|xy");
+    assert_eq!(optimized(any_of!(escape!("for"), escape!("foreach"), escape!("if"))), "for(?:|each)|if");
+    assert_eq!(optimized(any_of!(escape!("ab"), escape!("c"), escape!("ad"))), "ab|c|ad");
+    assert_eq!(optimized(sequence!(escape!("a"), capturing_group!(escape!("b")))), "a(b)");
+    assert_eq!(optimized(exactly!(char_class!['x'], 1)), "x");
+    assert_eq!(optimized(any_of!(escape!("ab"))), "ab");
+    let accents = ascii_only!(any_of!(escape!("é"), escape!("ü"), escape!("a"), escape!("b")));
+    assert_eq!(optimized(accents.clone()), "(?-u:é|ü|[ab])");
+    assert!(accents.optimize().compile().unwrap().is_match("ü"));
+}
+
+fn sample_part() -> impl proptest::strategy::Strategy<Value = RegexPart> {
+    use proptest::prelude::*;
+
+    // Single characters under `ascii_only!` are weighted up so merging
+    // them into classes gets exercised.
+    let leaf = prop_oneof![
+        2 => "[abcé]{0,3}".prop_map(|text| escape!(text)),
+        3 => "[aé]".prop_map(|text| escape!(text)),
+        1 => Just(char_class!["ab"]),
+        1 => Just(not_class!(char_class!['a'])),
+        1 => Just(nothing!()),
+        1 => Just(word_boundary!()),
+        1 => Just(anything!()),
+    ];
+    leaf.prop_recursive(4, 24, 4, |inner| {
+        prop_oneof![
+            2 => proptest::collection::vec(inner.clone(), 1..4).prop_map(RegexPart::sequence),
+            3 => proptest::collection::vec(inner.clone(), 1..4).prop_map(RegexPart::any_of),
+            2 => (inner.clone(), 0..3u32, proptest::option::of(0..3u32), any::<bool>()).prop_map(
+                |(part, min, extra, greedy)| part.repeated(min, extra.map(|extra| min + extra), greedy)
+            ),
+            1 => inner.clone().prop_map(|part| non_capturing_group!(part)),
+            1 => inner.clone().prop_map(|part| capturing_group!(part)),
+            1 => inner.clone().prop_map(|part| dot_matches_newline!(part)),
+            1 => inner.clone().prop_map(|part| case_insensitive!(part)),
+            1 => inner.clone().prop_map(|part| multiline!(part)),
+            2 => inner.clone().prop_map(|part| ascii_only!(part)),
+            2 => (inner, any::<bool>(), any::<bool>()).prop_map(|(part, unicode, insensitive)| part.with_flags(Flags {
+                unicode: Some(unicode),
+                case_insensitive: Some(insensitive),
+                ..Flags::default()
+            })),
+        ]
+    })
+}
+
+proptest::proptest! {
+    #[test]
+    fn optimize_preserves_matches(part in sample_part(), haystacks in proptest::collection::vec("[abcéA\n]{0,8}", 8)) {
+        // Without `u` some samples are invalid, like `(?-u:[^a])`.
+        let Ok(original) = part.compile() else { return Ok(()) };
+        let optimized = part.clone().optimize().compile().unwrap();
+        for haystack in &haystacks {
+            let spans = |regex: &CompiledRegex| -> Vec<Vec<_>> {
+                regex
+                    .captures_iter(haystack)
+                    .map(|captures| (0..captures.len()).map(|index| captures.get(index).map(|m| m.range())).collect())
+                    .collect()
+            };
+            proptest::prop_assert_eq!(spans(&original), spans(&optimized), "{} vs {} on {:?}", original, optimized, haystack);
+        }
+    }
+}
+
+#[test]
+fn keyword_trie() {
+    assert_eq!(keywords(["cat", "car", "cab"]).build().source(), "ca[brt]");
+    assert_eq!(keywords(["a", "ab", "abc", ""]).build().source(), "a(?:bc?)?");
+    assert_eq!(keywords(["a.b", "a+b"]).build().source(), r"a(?:\+b|\.b)");
+    assert_eq!(keywords(Vec::<String>::new()).build().source(), r"[^\s\S]");
+    assert_eq!(keywords(["If", "iF", "else"]).case_insensitive(true).build().source(), "(?i:else|if)");
+    let dotted = keywords(["İ", "Straße"]).case_insensitive(true).build().compile().unwrap();
+    assert!(dotted.is_match("İ") && dotted.is_match("STRAßE"));
+
+    let whole = keywords(["in", "int", "interface"]).whole_words(true).build().compile().unwrap();
+    let found: Vec<_> = whole.find_iter("int interface into in").map(|m| m.as_str()).collect();
+    assert_eq!(found, ["int", "interface", "in"]);
+
+    let loose = RegexPart::from(keywords(["in", "int"])).compile().unwrap();
+    assert_eq!(loose.find("into").unwrap().as_str(), "int");
+
+    let shouting = keywords(["select", "from"]).case_insensitive(true).whole_words(true).build();
+    let shouting = shouting.compile().unwrap();
+    let found: Vec<_> = shouting.find_iter("SELECT x From t").map(|m| m.as_str()).collect();
+    assert_eq!(found, ["SELECT", "From"]);
+}
+
+proptest::proptest! {
+    #[test]
+    fn keywords_match_like_longest_first_alternation(
+        words in proptest::collection::vec("[ab]{1,4}", 1..12),
+        haystack in "[ab ]{0,16}",
+    ) {
+        let mut sorted = words.clone();
+        sorted.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        let alternation = RegexPart::any_of(sorted.iter().map(|word| escape!(word))).compile().unwrap();
+        let trie = keywords(&words).build().compile().unwrap();
+        let spans = |regex: &CompiledRegex| regex.find_iter(&haystack).map(|m| m.range()).collect::<Vec<_>>();
+        proptest::prop_assert_eq!(spans(&trie), spans(&alternation), "{}", trie);
+    }
+}
+
+#[test]
+fn inline_flags() {
+    let word = one_or_more!(alpha_numeric!());
+    assert_eq!(case_insensitive!(word).source(), r"(?i:\w+)");
+    assert_eq!(case_insensitive!(sequence!(escape!("a"), case_sensitive!(escape!("b")))).source(), "(?i:a(?-i:b))");
+    assert_eq!(multiline!(dot_matches_newline!(anything!())).source(), "(?m:(?s:.))");
+    assert_eq!(multiline!(dot_matches_newline!(anything!())).optimize().source(), "(?ms:.)");
+    assert_eq!(swap_greed!(word).source(), r"(?U:\w+)");
+    assert_eq!(ignore_whitespace!(raw!("a b")).source(), "(?x:a b)");
+    assert_eq!(ascii_only!(digit!()).source(), r"(?-u:\d)");
+    let all_on = Flags {
+        case_insensitive: Some(true),
+        multi_line: Some(true),
+        dot_matches_new_line: Some(true),
+        ignore_whitespace: Some(true),
+        swap_greed: Some(false),
+        unicode: Some(false),
+    };
+    assert_eq!(anything!().with_flags(all_on).source(), "(?imsx-Uu:.)");
+
+    let matches = |part: RegexPart, haystack: &str| part.compile().unwrap().is_match(haystack);
+    assert!(matches(sequence!(case_insensitive!(escape!("ab")), escape!("c")), "ABc"));
+    assert!(!matches(sequence!(case_insensitive!(escape!("ab")), escape!("c")), "ABC"));
+    assert!(matches(multiline!(sequence!(begin!(), escape!("b"), end!())), "a\nb\nc"));
+    assert!(!matches(sequence!(begin!(), escape!("b"), end!()), "a\nb\nc"));
+    assert!(matches(dot_matches_newline!(sequence!(escape!("a"), anything!(), escape!("b"))), "a\nb"));
+    assert!(!matches(ascii_only!(digit!()), "٣"));
+    assert!(matches(digit!(), "٣"));
+    let lazy = swap_greed!(one_or_more!(digit!())).compile().unwrap();
+    assert_eq!(lazy.find("123").unwrap().as_str(), "1");
+}
+
+#[test]
+fn inline_flags_around_classes() {
+    type Wrap = fn(RegexPart) -> RegexPart;
+    let wrappers: [(&str, Wrap); 7] = [
+        ("case_insensitive", |part| case_insensitive!(part)),
+        ("case_sensitive", |part| case_sensitive!(part)),
+        ("multiline", |part| multiline!(part)),
+        ("dot_matches_newline", |part| dot_matches_newline!(part)),
+        ("ignore_whitespace", |part| ignore_whitespace!(part)),
+        ("swap_greed", |part| swap_greed!(part)),
+        ("ascii_only", |part| ascii_only!(part)),
+    ];
+    for (name, wrap) in wrappers {
+        let class = wrap(char_class!['#', ' ', 'a'..='c']).compile().unwrap();
+        assert!(["#", " ", "b"].iter().all(|text| class.is_match(text)) && !class.is_match("d"), "{}", name);
+        if name == "ascii_only" {
+            // `(?-u)` rejects the typographic marks outside ASCII.
+            assert!(wrap(char_class![marks!()]).compile().is_err());
+            assert!(wrap(custom_word_boundary!()).compile().is_err());
+            continue;
+        }
+        let marks = wrap(char_class![marks!()]).compile().unwrap();
+        assert!(MARKS.chars().all(|mark| marks.is_match(&mark.to_string())), "{}: {}", name, marks);
+        let boundary = wrap(custom_word_boundary!()).compile().unwrap();
+        assert!(boundary.is_match("a # b"), "{}", name);
+    }
+}
+
+#[test]
+fn compile_options() {
+    let word = sequence!(begin!(), escape!("ab"), end!());
+    let options = CompileOptions::new().case_insensitive(true).multi_line(true);
+    let regex = word.compile_with(&options).unwrap();
+    assert!(regex.is_match("x\nAB\ny"));
+    let strict = sequence!(begin!(), case_sensitive!(escape!("ab")), end!()).compile_with(&options).unwrap();
+    assert!(!strict.is_match("AB"));
+
+    let tiny = CompileOptions::new().size_limit(10);
+    assert!(matches!(exactly!(alpha_numeric!(), 100).compile_with(&tiny), Err(BuildError::Regex(_))));
+    let shallow = CompileOptions::new().nest_limit(2);
+    assert!(capturing_group!(capturing_group!(capturing_group!(digit!()))).compile_with(&shallow).is_err());
+
+    let ascii = one_or_more!(alpha_numeric!()).compile_with(&CompileOptions::new().unicode(false)).unwrap();
+    assert_eq!(ascii.find("слово word").unwrap().as_str(), "word");
+}
+
+#[test]
+fn explain() {
+    let part = sequence!(
+        begin!(),
+        any_of!(escape!("+"), escape!("-"), escape!("ab")),
+        lazy!(char_class!['a'..='f', "xy"]),
+        between!(not_class!(whitespace!()), 2, 3),
+        case_insensitive!(escape!("z")),
+        end!()
+    );
+    assert_eq!(
+        part.explain(),
+        "a sequence of:\n\
+        \x20 the start of the text\n\
+        \x20 one of:\n\
+        \x20   the character \"+\"\n\
+        \x20   the character \"-\"\n\
+        \x20   the text \"ab\"\n\
+        \x20 zero or more, as few as possible, of: one of 'a' to 'f', 'x' or 'y'\n\
+        \x20 between 2 and 3 of: non-whitespace\n\
+        \x20 case-insensitive: the character \"z\"\n\
+        \x20 the end of the text\n"
+    );
+
+    let nested = capturing_group!(sequence!(capturing_group!(digit!()), named_group!("rest", one_or_more!(alpha_numeric!()))));
+    assert_eq!(
+        nested.explain(),
+        "capture group 1:\n\
+        \x20 a sequence of:\n\
+        \x20   capture group 2: a digit\n\
+        \x20   capture group 3 \"rest\": one or more of: a word character\n"
+    );
+    assert_eq!(followed_by!(digit!()).explain(), "followed by: a digit\n");
+    assert_eq!(dot_matches_newline!(anything!()).explain(), "dot matching newlines: any character\n");
+    assert_eq!(
+        sequence!(anything!(), dot_matches_newline!(escape!("a")), anything!()).explain(),
+        "a sequence of:\n\
+        \x20 any character except a newline\n\
+        \x20 dot matching newlines: the character \"a\"\n\
+        \x20 any character except a newline\n"
+    );
+}
+
+#[test]
+fn railroad_svg() {
+    let part = sequence!(
+        named_group!("key", one_or_more!(alpha_numeric!())),
+        escape!("<="),
+        optional!(any_of!(escape!("a"), digit!()))
+    );
+    let svg = part.to_railroad_svg();
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg""#));
+    assert!(svg.ends_with("</svg>"));
+    assert!(svg.contains(r#">group 1 &quot;key&quot;</text>"#));
+    assert!(svg.contains(r#">&quot;&lt;=&quot;</text>"#));
+    assert!(svg.contains(">a digit</text>"));
+    assert!(!svg.contains("NaN"));
+    assert_eq!(svg, part.to_railroad_svg());
+    assert_eq!(svg.matches("<rect").count(), 5);
+}
+
+#[test]
+fn parse() {
+    let parse = |pattern| RegexPart::parse(pattern).unwrap();
+    assert_eq!(parse("ab+c"), sequence!(escape!("a"), one_or_more!(escape!("b")), escape!("c")));
+    assert_eq!(
+        parse(r"(?P<year>\d{4})-(?:\d\d?)*?"),
+        sequence!(
+            named_group!("year", exactly!(digit!(), 4)),
+            escape!("-"),
+            zero_or_more_lazy!(sequence!(digit!(), optional!(digit!())))
+        )
+    );
+    assert_eq!(
+        parse(r"[^a-c\s[:digit:]]|[\w&&\p{Greek}]|."),
+        any_of!(
+            not_class!(char_class!['a'..='c', whitespace!(), '0'..='9']),
+            class_intersection!(alpha_numeric!(), unicode_class!("Greek")),
+            anything!()
+        )
+    );
+    assert_eq!(parse("x(?i)y|z"), any_of!(sequence!(escape!("x"), case_insensitive!(escape!("y"))), case_insensitive!(escape!("z"))));
+    assert_eq!(parse(r"^\b\A$"), sequence!(begin!(), word_boundary!(), raw!(r"\A"), end!()));
+    assert_eq!(parse("").source(), "");
+
+    // Reparsing the rendered source gives the same tree.
+    for pattern in [r"(a|b)+?c{2,}[x-z--y]", r"(?i:a(?-i)b)(?s).\p{L}\PN", "[[:^alpha:]a]{0,3}"] {
+        let part = parse(pattern);
+        assert_eq!(RegexPart::parse(&part.source()).unwrap(), part, "{}", pattern);
+    }
+
+    assert!(matches!(RegexPart::parse("a(b"), Err(ParseError::Syntax(_))));
+    assert!(matches!(RegexPart::parse(r"\p{Klingon}"), Err(ParseError::Syntax(_))));
+    let error = RegexPart::parse("(?R)a$").unwrap_err();
+    assert!(matches!(error, ParseError::Unsupported(_)));
+    assert_eq!(error.to_string(), "the `R` flag is not supported by RegexPart");
+}
+
+proptest::proptest! {
+    #[test]
+    fn parse_preserves_matches(part in sample_part(), haystacks in proptest::collection::vec("[abcéA\n]{0,8}", 8)) {
+        // Without `u` some samples are invalid, like `(?-u:[^a])`.
+        let Ok(original) = part.compile() else { return Ok(()) };
+        let parsed = RegexPart::parse(&part.source()).unwrap().compile().unwrap();
+        for haystack in &haystacks {
+            let spans = |regex: &CompiledRegex| -> Vec<Vec<_>> {
+                regex
+                    .captures_iter(haystack)
+                    .map(|captures| (0..captures.len()).map(|index| captures.get(index).map(|m| m.range())).collect())
+                    .collect()
+            };
+            proptest::prop_assert_eq!(spans(&original), spans(&parsed), "{} vs {} on {:?}", original, parsed, haystack);
+        }
+    }
+}
+
+#[test]
+fn rust_code() {
+    let code = |pattern| RegexPart::parse(pattern).unwrap().to_rust_code();
+    assert_eq!(code("a"), r#"escape!("a")"#);
+    assert_eq!(code(r"\.\\"), r#"escape!(r".\")"#);
+    assert_eq!(code("(?i:ab)?"), r#"optional!(case_insensitive!(escape!("ab")))"#);
+    assert_eq!(code("(?-m:^)"), r#"raw!("(?-m:^)")"#);
+    assert_eq!(code(r"\Bx{2,5}?"), r#"sequence!(raw!(r"\B"), between_lazy!(escape!("x"), 2, 5))"#);
+    assert_eq!(code(r#"[^"\d\pL]"#), r#"not_class!(char_class!['"', digit!(), unicode_class!("L")])"#);
+    let url = r"(?P<scheme>https?)://(?P<host>[a-z0-9.-]+)(?::(?P<port>\d{1,5}))?";
+    assert_eq!(
+        code(url),
+        r#"sequence!(
+    named_group!("scheme", sequence!(escape!("http"), optional!(escape!("s")))),
+    escape!("://"),
+    named_group!("host", one_or_more!(char_class!['a'..='z', '0'..='9', ".-"])),
+    optional!(sequence!(escape!(":"), named_group!("port", between!(digit!(), 1, 5))))
+)"#
+    );
+    // The printed code builds the same tree.
+    assert_eq!(
+        RegexPart::parse(url).unwrap(),
+        sequence!(
+            named_group!("scheme", sequence!(escape!("http"), optional!(escape!("s")))),
+            escape!("://"),
+            named_group!("host", one_or_more!(char_class!['a'..='z', '0'..='9', ".-"])),
+            optional!(sequence!(escape!(":"), named_group!("port", between!(digit!(), 1, 5))))
+        )
+    );
+}
+
+#[test]
+fn generate() {
+    use rand::SeedableRng;
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    let limits = GenerateLimits::new();
+    for part in [
+        patterns::email(),
+        patterns::ipv6(),
+        patterns::semver(),
+        patterns::quoted_string('\''),
+        sequence!(word_boundary!(), keywords(["if", "else"]).build(), word_boundary!()),
+        case_insensitive!(sequence!(escape!("select"), one_or_more!(whitespace!()), char_class!['a'..='c'])),
+        RegexPart::parse(r"(?-u:\w)+\p{Greek}[[:punct:]]$").unwrap(),
+    ] {
+        let whole = sequence!(begin!(), part.clone(), end!()).compile().unwrap();
+        for _ in 0..20 {
+            let example = part.generate(&mut rng, &limits).unwrap();
+            assert!(whole.is_match(&example), "{:?} does not match {}", example, part.source());
+        }
+    }
+
+    // Repetitions stay within their bounds and the cap.
+    let capped = GenerateLimits::new().repeat_cap(2);
+    for _ in 0..50 {
+        let digits = one_or_more!(digit!()).generate(&mut rng, &capped).unwrap();
+        assert!((1..=3).contains(&digits.chars().count()), "{:?}", digits);
+        let letters = between!(escape!("x"), 2, 9).generate(&mut rng, &capped).unwrap();
+        assert!((2..=4).contains(&letters.len()), "{:?}", letters);
+        assert_eq!(exactly!(escape!("ab"), 3).generate(&mut rng, &capped).unwrap(), "ababab");
+    }
+
+    // Impossible branches are avoided, impossible patterns give up.
+    let empty = not_class!(char_class![whitespace!(), not_class!(whitespace!())]);
+    assert_eq!(any_of!(empty.clone(), escape!("b")).generate(&mut rng, &limits).unwrap(), "b");
+    assert_eq!(empty.generate(&mut rng, &limits), None);
+    assert_eq!(sequence!(escape!("a"), begin!()).generate(&mut rng, &limits), None);
+
+    // Ranges spanning the surrogate gap pick each character about equally.
+    let gap = char_class!['\u{D7FE}'..='\u{E001}'];
+    let mut counts = std::collections::HashMap::new();
+    for _ in 0..400 {
+        *counts.entry(gap.generate(&mut rng, &limits).unwrap()).or_insert(0) += 1;
+    }
+    assert_eq!(counts.len(), 4, "{:?}", counts);
+    assert!(counts.values().all(|&count| count > 50), "{:?}", counts);
+}
+
+#[test]
+fn shrink() {
+    let pair = sequence!(one_or_more!(alpha_numeric!()), escape!("="), one_or_more!(digit!()));
+    assert_eq!(pair.shrink("key=1789", |_| true), "a=0");
+    assert_eq!(pair.shrink("key=1789", |text| text.contains("78")), "a=78");
+    assert_eq!(pair.shrink("key=17 89", |_| true), "key=17 89");
+    assert_eq!(sequence!(at_least!(escape!("x"), 2), optional!(escape!("y"))).shrink("xxxxy", |_| true), "xx");
+}
+
+#[test]
+fn lookaround_rendering() {
+    assert_eq!(followed_by!(digit!()).source(), r"(?=\d)");
+    assert_eq!(not_followed_by!(digit!()).source(), r"(?!\d)");
+    assert_eq!(preceded_by!(escape!("$")).source(), r"(?<=\$)");
+    assert_eq!(not_preceded_by!(digit!()).source(), r"(?<!\d)");
+    assert_eq!(not_escaped!(escape!("*")).source(), r"(?<!\\)\*");
+}
+
+#[test]
+#[cfg(not(feature = "fancy"))]
+fn lookaround_requires_fancy() {
+    let error = sequence!(digit!(), followed_by!(alpha_numeric!())).compile().unwrap_err();
+    assert!(matches!(error, BuildError::Unsupported("lookaround")));
+    assert_eq!(error.to_string(), "lookaround requires the `fancy` feature of RegexLib");
+}
+
+#[test]
+#[cfg(feature = "fancy")]
+fn lookaround_with_fancy() {
+    let price = sequence!(preceded_by!(escape!("$")), one_or_more!(digit!())).compile().unwrap();
+    assert_eq!(price.find("cost: $42").unwrap().range(), 7..9);
+    assert!(!price.is_match("cost: 42"));
+
+    let word = sequence!(one_or_more!(alpha_numeric!()), not_followed_by!(escape!("(")));
+    let word = sequence!(word_boundary!(), word).compile().unwrap();
+    let words: Vec<_> = word.find_iter("call(x) y").map(|m| m.as_str()).collect();
+    assert_eq!(words, ["cal", "x", "y"]);
+
+    let star = not_escaped!(escape!("*")).compile().unwrap();
+    assert_eq!(star.replace_all(r"a\*b*", "+"), r"a\*b+");
+    assert_eq!(star.split(r"a*b\*c").collect::<Vec<_>>(), ["a", r"b\*c"]);
+
+    let pair = sequence!(
+        named_group!("key", one_or_more!(alpha_numeric!())),
+        followed_by!(escape!("="))
+    )
+    .compile()
+    .unwrap();
+    let keys: Vec<_> = pair.captures_iter("a=1 b=2 c").map(|c| c["key"].to_string()).collect();
+    assert_eq!(keys, ["a", "b"]);
+
+    let options = CompileOptions::new().case_insensitive(true).backtrack_limit(1_000);
+    let shout = sequence!(escape!("a"), followed_by!(escape!("b"))).compile_with(&options).unwrap();
+    assert_eq!(shout.find("xAB").unwrap().range(), 1..2);
+
+    let call = sequence!(one_or_more!(alpha_numeric!()), followed_by!(escape!("(")));
+    let lexer = Lexer::new([(Some("call"), call.clone())], "other").unwrap();
+    let tokens: Vec<_> = lexer.tokens("f(x)").map(|(token, _, text)| (token, text)).collect();
+    assert_eq!(tokens, [("call", "f"), ("other", "(x)")]);
+
+    let set = RegexPartSet::labeled([("digit", digit!()), ("call", call.clone()), ("word", alpha_numeric!())]).unwrap();
+    assert_eq!(set.matching_labels("f(x)").collect::<Vec<_>>(), ["call", "word"]);
+    assert_eq!(set.matches("12").collect::<Vec<_>>(), [0, 2]);
+
+    let options = CompileOptions::new().backtrack_limit(1_000);
+    let runs = one_or_more!(capturing_group!(one_or_more!(escape!("a"))));
+    let blowup = sequence!(runs, backref!(1), escape!("b")).compile_with(&options).unwrap();
+    let haystack = "a".repeat(30);
+    assert!(matches!(blowup.try_is_match(&haystack), Err(SearchError::Fancy(_))));
+    assert!(blowup.try_find(&haystack).is_err() && blowup.try_captures(&haystack).is_err());
+    assert!(!blowup.is_match(&haystack) && blowup.find(&haystack).is_none());
+    assert_eq!(blowup.try_find("aab").unwrap().unwrap().as_str(), "aab");
+    let mixed = format!("aab {} aab", haystack);
+    assert!(blowup.try_replace_all(&mixed, "x").is_err());
+    assert_eq!(blowup.replace_all(&mixed, "x"), mixed);
+    let mut matches = blowup.find_iter(&mixed);
+    assert_eq!(matches.next().map(|m| m.range()), Some(0..3));
+    assert!(matches.next().is_none() && matches.next().is_none());
+    assert_eq!(blowup.captures_iter(&mixed).count(), 1);
+    assert_eq!(blowup.split(&mixed).collect::<Vec<_>>(), ["", &mixed[3..]]);
+    let error = followed_by!(digit!()).compile_with(&CompileOptions::new().nest_limit(10)).unwrap_err();
+    assert_eq!(error.to_string(), "`nest_limit` is not supported for patterns with lookaround");
+
+    let constant = regex_const! { sequence!(not_preceded_by!(digit!()), one_or_more!(digit!())) };
+    assert_eq!(constant.find("x12").unwrap().as_str(), "12");
+
+    use rand::SeedableRng;
+    let odd = sequence!(one_or_more!(digit!()), preceded_by!(char_class!["13579"]));
+    let example = odd.generate(&mut rand::rngs::StdRng::seed_from_u64(3), &GenerateLimits::new()).unwrap();
+    assert!("13579".contains(example.chars().last().unwrap()), "{:?}", example);
+}
+
+#[test]
+fn backref_rendering() {
+    let doubled = sequence!(capturing_group!(alpha_numeric!()), backref!(1));
+    assert_eq!(doubled.source(), r"(\w)\k<1>");
+    assert_eq!(sequence!(backref!(1), digit!()).source(), r"\k<1>\d");
+    assert_eq!(one_or_more!(backref!("tag")).source(), r"\k<tag>+");
+    assert!(doubled.node().has_backref());
+    assert!(!doubled.node().has_lookaround());
+    assert_eq!(doubled.to_rust_code(), "sequence!(capturing_group!(alpha_numeric!()), backref!(1))");
+    assert_eq!(backref!("tag").to_rust_code(), r#"backref!("tag")"#);
+    assert_eq!(backref!(2).explain(), "the text matched by capture group 2\n");
+    assert!(backref!("tag").to_railroad_svg().contains("same as capture group &quot;tag&quot;"));
+}
+
+#[test]
+#[cfg(not(feature = "fancy"))]
+fn backref_requires_fancy() {
+    let error = sequence!(capturing_group!(digit!()), backref!(1)).compile().unwrap_err();
+    assert!(matches!(error, BuildError::Unsupported("a backreference")));
+    assert_eq!(error.to_string(), "a backreference requires the `fancy` feature of RegexLib");
+}
+
+#[test]
+#[cfg(feature = "fancy")]
+fn backref_with_fancy() {
+    let word = capturing_group!(one_or_more!(alpha_numeric!()));
+    let repeated = sequence!(word_boundary!(), word, escape!(" "), backref!(1), word_boundary!()).compile().unwrap();
+    assert_eq!(repeated.find("it is is fine").unwrap().as_str(), "is is");
+    assert!(!repeated.is_match("this is fine"));
+
+    let quoted = sequence!(
+        named_group!("quote", char_class!["'\""]),
+        named_group!("text", zero_or_more_lazy!(anything!())),
+        backref!("quote")
+    );
+    let quoted = quoted.compile().unwrap();
+    let texts: Vec<_> = quoted.captures_iter(r#"'a"b' "c'd""#).map(|c| c["text"].to_string()).collect();
+    assert_eq!(texts, [r#"a"b"#, "c'd"]);
+
+    let constant = regex_const! { sequence!(capturing_group!(digit!()), backref!(1)) };
+    assert_eq!(constant.find("1233").unwrap().as_str(), "33");
+
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+    let tag = sequence!(
+        escape!("<"),
+        named_group!("tag", one_or_more!(char_class!['a'..='z'])),
+        escape!(">"),
+        zero_or_more!(digit!()),
+        escape!("</"),
+        backref!("tag"),
+        escape!(">")
+    );
+    for _ in 0..20 {
+        let example = tag.generate(&mut rng, &GenerateLimits::new()).unwrap();
+        let name = &example[1..example.find('>').unwrap()];
+        assert!(example.ends_with(&format!("</{}>", name)), "{:?}", example);
+    }
+
+    let pair = sequence!(optional!(capturing_group!(digit!())), backref!(1));
+    let example: Vec<char> = pair.generate(&mut rng, &GenerateLimits::new()).unwrap().chars().collect();
+    assert!(matches!(example[..], [first, second] if first == second), "{:?}", example);
+
+    // Groups are numbered the same way wherever the walk goes.
+    let pairs = sequence!(
+        any_of!(capturing_group!(escape!("a")), capturing_group!(escape!("b"))),
+        zero_or_more!(capturing_group!(escape!("-"))),
+        capturing_group!(one_or_more!(digit!())),
+        backref!(4)
+    );
+    for _ in 0..20 {
+        let example = pairs.generate(&mut rng, &GenerateLimits::new()).unwrap();
+        let digits = example.trim_start_matches(['a', 'b', '-']);
+        let (first, second) = digits.split_at(digits.len() / 2);
+        assert_eq!(first, second, "{:?}", example);
+    }
+}
+
+#[test]
+fn regex_const_matches() {
+    let word = regex_const! { one_or_more!(alpha_numeric!()) };
+    assert_eq!(word.find("  hi ").unwrap().as_str(), "hi");
+    assert!(std::ptr::eq(word.regex(), word.regex()));
+}
@@ -0,0 +1,153 @@
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// Why `parse` on a `regex_struct!` type failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchError {
+    /// The input does not match the pattern as a whole.
+    NoMatch,
+    /// The pattern matched, but the group for this field took no part in
+    /// it. Fields of type `Option<T>` are `None` instead.
+    MissingGroup(&'static str),
+    /// The group matched `value`, which the field type failed to parse.
+    Parse { field: &'static str, value: String, message: String },
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchError::NoMatch => f.write_str("input does not match the pattern"),
+            MatchError::MissingGroup(field) => write!(f, "group `{}` did not participate in the match", field),
+            MatchError::Parse { field, value, message } => {
+                write!(f, "can not parse `{}` for field `{}`: {}", value, field, message)
+            }
+        }
+    }
+}
+
+impl Error for MatchError {}
+
+/// Parses the group of a `regex_struct!` field of type `T`. The macro calls
+/// `(&Field::<T>::new()).parse_group(..)`, which picks `OptionalField` for
+/// `Option<T>` and `RequiredField` for everything else.
+#[doc(hidden)]
+pub struct Field<T>(PhantomData<T>);
+
+impl<T> Field<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Field(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait OptionalField<T> {
+    fn parse_group(&self, field: &'static str, value: Option<&str>) -> Result<Option<T>, MatchError>;
+}
+
+impl<T: FromStr> OptionalField<T> for Field<Option<T>>
+where
+    T::Err: fmt::Display,
+{
+    fn parse_group(&self, field: &'static str, value: Option<&str>) -> Result<Option<T>, MatchError> {
+        value.map(|value| parse(field, value)).transpose()
+    }
+}
+
+#[doc(hidden)]
+pub trait RequiredField<T> {
+    fn parse_group(&self, field: &'static str, value: Option<&str>) -> Result<T, MatchError>;
+}
+
+impl<T: FromStr> RequiredField<T> for &Field<T>
+where
+    T::Err: fmt::Display,
+{
+    fn parse_group(&self, field: &'static str, value: Option<&str>) -> Result<T, MatchError> {
+        parse(field, value.ok_or(MatchError::MissingGroup(field))?)
+    }
+}
+
+fn parse<T: FromStr>(field: &'static str, value: &str) -> Result<T, MatchError>
+where
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|error: T::Err| MatchError::Parse {
+        field,
+        value: value.to_string(),
+        message: error.to_string(),
+    })
+}
+
+/// Declares a struct filled from the named groups of a pattern.
+///
+/// Every field needs a `named_group!` of the same name in the pattern and
+/// is parsed with `FromStr`. The whole input must match. A field of type
+/// `Option<T>` is `None` when its group takes no part in the match.
+///
+/// ```
+/// use RegexLib::*;
+///
+/// regex_struct! {
+///     #[derive(Debug, PartialEq)]
+///     pub struct Version {
+///         pub major: u32,
+///         pub minor: u32,
+///         pub patch: Option<u32>,
+///     } = sequence!(
+///         named_group!("major", one_or_more!(digit!())),
+///         escape!("."),
+///         named_group!("minor", one_or_more!(digit!())),
+///         optional!(sequence!(escape!("."), named_group!("patch", one_or_more!(digit!()))))
+///     );
+/// }
+///
+/// assert_eq!(Version::parse("1.42"), Ok(Version { major: 1, minor: 42, patch: None }));
+/// assert_eq!(Version::parse("1.42.7"), Ok(Version { major: 1, minor: 42, patch: Some(7) }));
+/// assert_eq!("1.x".parse::<Version>(), Err(MatchError::NoMatch));
+/// ```
+#[macro_export]
+macro_rules! regex_struct {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($field_vis:vis $field:ident : $ty:ty),+ $(,)?
+        } = $pattern:expr;
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $($field_vis $field: $ty),+
+        }
+
+        impl $name {
+            /// The pattern anchored to the whole input, compiled on first use.
+            pub fn regex() -> &'static $crate::CompiledRegex {
+                $crate::static_regex!($crate::sequence!($crate::begin!(), $pattern, $crate::end!()))
+            }
+
+            pub fn parse(text: &str) -> ::core::result::Result<Self, $crate::MatchError> {
+                #[allow(unused_imports)]
+                use $crate::{OptionalField as _, RequiredField as _};
+
+                let captures = Self::regex().captures(text).ok_or($crate::MatchError::NoMatch)?;
+                ::core::result::Result::Ok($name {
+                    $($field: {
+                        let field = ::core::stringify!($field);
+                        let value = captures.name(field).map(|group| group.as_str());
+                        (&$crate::Field::<$ty>::new()).parse_group(field, value)?
+                    }),+
+                })
+            }
+        }
+
+        impl ::core::str::FromStr for $name {
+            type Err = $crate::MatchError;
+
+            fn from_str(text: &str) -> ::core::result::Result<Self, $crate::MatchError> {
+                $name::parse(text)
+            }
+        }
+    };
+}
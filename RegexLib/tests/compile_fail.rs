@@ -0,0 +1,8 @@
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/compile_fail/*.rs");
+    if cfg!(not(feature = "fancy")) {
+        t.compile_fail("tests/compile_fail/without_fancy/*.rs");
+    }
+}
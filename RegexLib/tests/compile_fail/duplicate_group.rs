@@ -0,0 +1,7 @@
+use RegexLib::regex_const;
+
+fn main() {
+    let _ = regex_const! {
+        sequence!(named_group!("n", digit!()), named_group!("n", digit!()))
+    };
+}
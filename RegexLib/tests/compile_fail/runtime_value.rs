@@ -0,0 +1,6 @@
+use RegexLib::{digit, regex_const};
+
+fn main() {
+    let part = digit!();
+    let _ = regex_const! { one_or_more!(part) };
+}
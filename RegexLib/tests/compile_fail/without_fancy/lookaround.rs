@@ -0,0 +1,5 @@
+use RegexLib::regex_const;
+
+fn main() {
+    let _ = regex_const! { sequence!(not!(digit!()), alpha_numeric!()) };
+}
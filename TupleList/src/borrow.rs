@@ -0,0 +1,92 @@
+use crate::{AsTupleOfRefs, Tuple, TupleList};
+
+/// Borrow the element through a shared reference.
+pub struct Shared;
+
+/// Borrow the element through a mutable reference.
+pub struct Mut;
+
+/// Leave the element out of the result.
+pub struct Skip;
+
+/// Implemented for tuple lists of mutable references, narrowing each one
+/// according to the matching entry of the `Spec` tuple list.
+pub trait BorrowSpec<Spec: TupleList>: TupleList {
+    type Output: TupleList;
+
+    fn apply(self) -> Self::Output;
+}
+
+impl BorrowSpec<()> for () {
+    type Output = ();
+
+    fn apply(self) {}
+}
+
+impl<'a, Head, Tail, SpecTail> BorrowSpec<(Shared, SpecTail)> for (&'a mut Head, Tail) where
+    Self: TupleList,
+    Tail: BorrowSpec<SpecTail>,
+    SpecTail: TupleList,
+    (Shared, SpecTail): TupleList,
+    (&'a Head, Tail::Output): TupleList,
+{
+    type Output = (&'a Head, Tail::Output);
+
+    fn apply(self) -> Self::Output {
+        (self.0, self.1.apply())
+    }
+}
+
+impl<'a, Head, Tail, SpecTail> BorrowSpec<(Mut, SpecTail)> for (&'a mut Head, Tail) where
+    Self: TupleList,
+    Tail: BorrowSpec<SpecTail>,
+    SpecTail: TupleList,
+    (Mut, SpecTail): TupleList,
+    (&'a mut Head, Tail::Output): TupleList,
+{
+    type Output = (&'a mut Head, Tail::Output);
+
+    fn apply(self) -> Self::Output {
+        (self.0, self.1.apply())
+    }
+}
+
+impl<Head, Tail, SpecTail> BorrowSpec<(Skip, SpecTail)> for (&mut Head, Tail) where
+    Self: TupleList,
+    Tail: BorrowSpec<SpecTail>,
+    SpecTail: TupleList,
+    (Skip, SpecTail): TupleList,
+{
+    type Output = Tail::Output;
+
+    fn apply(self) -> Self::Output {
+        self.1.apply()
+    }
+}
+
+type MutRefsList<'a, T> = <<T as AsTupleOfRefs<'a>>::TupleOfMutRefs as Tuple>::TupleList;
+
+/// Result of `split_borrow::<Spec>()` on the tuple `T`.
+pub type SplitBorrowed<'a, T, Spec> =
+    <<MutRefsList<'a, T> as BorrowSpec<<Spec as Tuple>::TupleList>>::Output as TupleList>::Tuple;
+
+/// Borrows the elements of a tuple in different ways at once.
+///
+/// ```
+/// use TupleList::{SplitBorrow, Shared, Mut, Skip};
+///
+/// let mut tuple = (1, String::from("abc"), false);
+/// let (len, text) = tuple.split_borrow::<(Shared, Mut, Skip)>();
+/// text.push_str(&len.to_string());
+/// assert_eq!(tuple.1, "abc1");
+/// ```
+pub trait SplitBorrow<'a>: AsTupleOfRefs<'a> {
+    fn split_borrow<Spec>(&'a mut self) -> SplitBorrowed<'a, Self, Spec> where
+        Spec: Tuple,
+        MutRefsList<'a, Self>: BorrowSpec<Spec::TupleList>,
+    {
+        self.as_tuple_of_mut_refs().into_tuple_list().apply().into_tuple()
+    }
+}
+
+impl<'a, T: AsTupleOfRefs<'a>> SplitBorrow<'a> for T {}
@@ -0,0 +1,120 @@
+use core::marker::PhantomData;
+
+use crate::TupleList;
+
+/// Builder slot which has not been filled yet.
+pub struct Missing<T>(PhantomData<T>);
+
+/// Builder slot holding its value.
+pub struct Present<T>(pub T);
+
+impl<T> Default for Missing<T> {
+    fn default() -> Self { Missing(PhantomData) }
+}
+
+/// Type-level index pointing at the head of a tuple list.
+pub struct Here;
+
+/// Type-level index pointing somewhere into the tail of a tuple list.
+pub struct There<Index>(PhantomData<Index>);
+
+/// Replaces the `Missing<T>` slot found at `Index` with `Present<T>`.
+///
+/// `Index` is inferred by the compiler, so a state can only be filled
+/// once per slot and only with the type it declares.
+pub trait SetSlot<T, Index>: TupleList {
+    type Output: TupleList;
+
+    fn set_slot(self, value: T) -> Self::Output;
+}
+
+impl<T, Tail> SetSlot<T, Here> for (Missing<T>, Tail) where
+    Self: TupleList,
+    (Present<T>, Tail): TupleList,
+{
+    type Output = (Present<T>, Tail);
+
+    fn set_slot(self, value: T) -> Self::Output {
+        (Present(value), self.1)
+    }
+}
+
+impl<Head, Tail, T, Index> SetSlot<T, There<Index>> for (Head, Tail) where
+    Self: TupleList,
+    Tail: SetSlot<T, Index>,
+    (Head, Tail::Output): TupleList,
+{
+    type Output = (Head, Tail::Output);
+
+    fn set_slot(self, value: T) -> Self::Output {
+        (self.0, self.1.set_slot(value))
+    }
+}
+
+/// Implemented for builder states where every slot is `Present`.
+pub trait Complete: TupleList {
+    type Values: TupleList;
+
+    fn into_values(self) -> Self::Values;
+}
+
+impl Complete for () {
+    type Values = ();
+
+    fn into_values(self) {}
+}
+
+impl<T, Tail> Complete for (Present<T>, Tail) where
+    Self: TupleList,
+    Tail: Complete,
+    (T, Tail::Values): TupleList,
+{
+    type Values = (T, Tail::Values);
+
+    fn into_values(self) -> Self::Values {
+        (self.0.0, self.1.into_values())
+    }
+}
+
+/// Typestate builder over a tuple list of `Missing`/`Present` slots.
+///
+/// ```
+/// use TupleList::{tuple_list_type, Builder, Missing};
+///
+/// let built = Builder::<tuple_list_type!(Missing<i32>, Missing<&str>)>::new()
+///     .set("abc")
+///     .set::<i32, _>(1)
+///     .build();
+/// assert_eq!(built, (1, "abc"));
+/// ```
+///
+/// `build()` only exists once every slot has been set. When the same type
+/// appears in several slots `set` can not infer which one to fill, so such
+/// states are rejected at compile time.
+pub struct Builder<State: TupleList> {
+    state: State,
+}
+
+impl<State: TupleList + Default> Builder<State> {
+    pub fn new() -> Self {
+        Builder { state: State::default() }
+    }
+}
+
+impl<State: TupleList + Default> Default for Builder<State> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<State: TupleList> Builder<State> {
+    pub fn set<T, Index>(self, value: T) -> Builder<State::Output> where
+        State: SetSlot<T, Index>,
+    {
+        Builder { state: self.state.set_slot(value) }
+    }
+
+    pub fn build(self) -> <State::Values as TupleList>::Tuple where
+        State: Complete,
+    {
+        self.state.into_values().into_tuple()
+    }
+}
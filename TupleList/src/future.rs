@@ -0,0 +1,282 @@
+use std::convert::Infallible;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::{Tuple, TupleList};
+
+/// Future which has either not finished yet or whose result is kept until
+/// every other future of the tuple is done.
+pub enum MaybeDone<F, T> {
+    Pending(Pin<Box<F>>),
+    Done(T),
+    Taken,
+}
+
+// Futures are boxed and results are never pinned, so moving the state is fine.
+impl<F, T> Unpin for MaybeDone<F, T> {}
+
+impl<F, T> MaybeDone<F, T> {
+    fn take(&mut self) -> T {
+        match mem::replace(self, MaybeDone::Taken) {
+            MaybeDone::Done(value) => value,
+            MaybeDone::Taken => panic!("future result taken twice"),
+            MaybeDone::Pending(_) => panic!("future result taken before completion"),
+        }
+    }
+}
+
+/// Tuple list of futures which can be awaited together.
+pub trait JoinFutures: TupleList {
+    type State: Unpin;
+    type Output: TupleList;
+
+    fn start(self) -> Self::State;
+    fn poll_ready(state: &mut Self::State, cx: &mut Context<'_>) -> bool;
+    fn take(state: &mut Self::State) -> Self::Output;
+}
+
+impl JoinFutures for () {
+    type State = ();
+    type Output = ();
+
+    fn start(self) {}
+    fn poll_ready(_: &mut (), _: &mut Context<'_>) -> bool { true }
+    fn take(_: &mut ()) {}
+}
+
+impl<F, Tail> JoinFutures for (F, Tail) where
+    F: Future,
+    Tail: JoinFutures,
+    Self: TupleList,
+    (F::Output, Tail::Output): TupleList,
+{
+    type State = (MaybeDone<F, F::Output>, Tail::State);
+    type Output = (F::Output, Tail::Output);
+
+    fn start(self) -> Self::State {
+        (MaybeDone::Pending(Box::pin(self.0)), self.1.start())
+    }
+
+    fn poll_ready(state: &mut Self::State, cx: &mut Context<'_>) -> bool {
+        if let MaybeDone::Pending(future) = &mut state.0 {
+            if let Poll::Ready(value) = future.as_mut().poll(cx) {
+                state.0 = MaybeDone::Done(value);
+            }
+        }
+        let tail_ready = Tail::poll_ready(&mut state.1, cx);
+        matches!(state.0, MaybeDone::Done(_)) && tail_ready
+    }
+
+    fn take(state: &mut Self::State) -> Self::Output {
+        (state.0.take(), Tail::take(&mut state.1))
+    }
+}
+
+/// Tuple list of futures resolving to `Result<_, E>` which can be awaited
+/// together, stopping at the first error.
+pub trait TryJoinFutures<E>: TupleList {
+    type State: Unpin;
+    type Output: TupleList;
+
+    fn start(self) -> Self::State;
+    fn poll_ready(state: &mut Self::State, cx: &mut Context<'_>) -> Result<bool, E>;
+    fn take(state: &mut Self::State) -> Self::Output;
+}
+
+impl<E> TryJoinFutures<E> for () {
+    type State = ();
+    type Output = ();
+
+    fn start(self) {}
+    fn poll_ready(_: &mut (), _: &mut Context<'_>) -> Result<bool, E> { Ok(true) }
+    fn take(_: &mut ()) {}
+}
+
+impl<F, T, E, Tail> TryJoinFutures<E> for (F, Tail) where
+    F: Future<Output = Result<T, E>>,
+    Tail: TryJoinFutures<E>,
+    Self: TupleList,
+    (T, Tail::Output): TupleList,
+{
+    type State = (MaybeDone<F, T>, Tail::State);
+    type Output = (T, Tail::Output);
+
+    fn start(self) -> Self::State {
+        (MaybeDone::Pending(Box::pin(self.0)), self.1.start())
+    }
+
+    fn poll_ready(state: &mut Self::State, cx: &mut Context<'_>) -> Result<bool, E> {
+        if let MaybeDone::Pending(future) = &mut state.0 {
+            if let Poll::Ready(result) = future.as_mut().poll(cx) {
+                state.0 = MaybeDone::Done(result?);
+            }
+        }
+        let tail_ready = Tail::poll_ready(&mut state.1, cx)?;
+        Ok(matches!(state.0, MaybeDone::Done(_)) && tail_ready)
+    }
+
+    fn take(state: &mut Self::State) -> Self::Output {
+        (state.0.take(), Tail::take(&mut state.1))
+    }
+}
+
+/// Output of `select_any`: `Head` when the first future finished first,
+/// otherwise the winner is somewhere in `Tail`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Choice<Head, Tail> {
+    Head(Head),
+    Tail(Tail),
+}
+
+pub trait ChoiceIndex {
+    /// Position of the finished future in the original tuple.
+    fn index(&self) -> usize;
+}
+
+impl ChoiceIndex for Infallible {
+    fn index(&self) -> usize { match *self {} }
+}
+
+impl<Head, Tail: ChoiceIndex> ChoiceIndex for Choice<Head, Tail> {
+    fn index(&self) -> usize {
+        match self {
+            Choice::Head(_) => 0,
+            Choice::Tail(tail) => tail.index() + 1,
+        }
+    }
+}
+
+/// Tuple list of futures racing against each other.
+///
+/// Not implemented for the empty tuple list, whose race could never end.
+pub trait SelectFutures: TupleList {
+    type State: Unpin;
+    type Output: ChoiceIndex;
+
+    fn start(self) -> Self::State;
+    fn poll_any(state: &mut Self::State, cx: &mut Context<'_>) -> Poll<Self::Output>;
+}
+
+impl<F> SelectFutures for (F, ()) where
+    F: Future,
+    Self: TupleList,
+{
+    type State = Pin<Box<F>>;
+    type Output = Choice<F::Output, Infallible>;
+
+    fn start(self) -> Self::State {
+        Box::pin(self.0)
+    }
+
+    fn poll_any(state: &mut Self::State, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        state.as_mut().poll(cx).map(Choice::Head)
+    }
+}
+
+impl<F, Next, Tail> SelectFutures for (F, (Next, Tail)) where
+    F: Future,
+    (Next, Tail): SelectFutures,
+    Self: TupleList,
+{
+    type State = (Pin<Box<F>>, <(Next, Tail) as SelectFutures>::State);
+    type Output = Choice<F::Output, <(Next, Tail) as SelectFutures>::Output>;
+
+    fn start(self) -> Self::State {
+        (Box::pin(self.0), self.1.start())
+    }
+
+    fn poll_any(state: &mut Self::State, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(value) = state.0.as_mut().poll(cx) {
+            return Poll::Ready(Choice::Head(value));
+        }
+        <(Next, Tail)>::poll_any(&mut state.1, cx).map(Choice::Tail)
+    }
+}
+
+/// Future returned by `join_all`.
+pub struct Join<L: JoinFutures> {
+    state: L::State,
+    done: bool,
+}
+
+impl<L: JoinFutures> Future for Join<L> {
+    type Output = <L::Output as TupleList>::Tuple;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        assert!(!this.done, "`Join` polled after completion");
+        if L::poll_ready(&mut this.state, cx) {
+            this.done = true;
+            Poll::Ready(L::take(&mut this.state).into_tuple())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Future returned by `try_join`.
+pub struct TryJoin<L: TryJoinFutures<E>, E> {
+    state: L::State,
+    done: bool,
+    error: PhantomData<fn() -> E>,
+}
+
+impl<L: TryJoinFutures<E>, E> Future for TryJoin<L, E> {
+    type Output = Result<<L::Output as TupleList>::Tuple, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        assert!(!this.done, "`TryJoin` polled after completion");
+        let result = match L::poll_ready(&mut this.state, cx) {
+            Ok(true) => Ok(L::take(&mut this.state).into_tuple()),
+            Ok(false) => return Poll::Pending,
+            Err(error) => Err(error),
+        };
+        this.done = true;
+        Poll::Ready(result)
+    }
+}
+
+/// Future returned by `select_any`.
+pub struct Select<L: SelectFutures> {
+    state: L::State,
+}
+
+impl<L: SelectFutures> Future for Select<L> {
+    type Output = L::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        L::poll_any(&mut self.get_mut().state, cx)
+    }
+}
+
+/// Arity-generic combinators for tuples of futures.
+///
+/// Futures are polled in tuple order, so `select_any` favours earlier
+/// elements when several are ready at once. `select_any` needs at least
+/// one future. Polling a `Join` or `TryJoin` again after it finished
+/// panics.
+pub trait FutureTuple: Tuple {
+    fn join_all(self) -> Join<Self::TupleList> where
+        Self::TupleList: JoinFutures,
+    {
+        Join { state: self.into_tuple_list().start(), done: false }
+    }
+
+    fn try_join<E>(self) -> TryJoin<Self::TupleList, E> where
+        Self::TupleList: TryJoinFutures<E>,
+    {
+        TryJoin { state: self.into_tuple_list().start(), done: false, error: PhantomData }
+    }
+
+    fn select_any(self) -> Select<Self::TupleList> where
+        Self::TupleList: SelectFutures,
+    {
+        Select { state: self.into_tuple_list().start() }
+    }
+}
+
+impl<T: Tuple> FutureTuple for T {}
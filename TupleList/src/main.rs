@@ -1,16 +1,17 @@
 // main.rs
-mod tuple;
-// Import traits and macros from the module
-use tuple::{Tuple, AsTupleOfRefs, TupleCons, NonEmptyTuple};
+use std::f64::consts::PI;
+
+// Import traits and macros from the library
+use TupleList::{tuple_list, tuple_list_type, Tuple, AsTupleOfRefs, TupleCons, NonEmptyTuple, Builder, Missing};
 
 fn main() {
     // Example 1: Creating a tuple and converting it to a tuple list
-    let tuple: (i32, f64, char) = (42, 3.14, 'A');
+    let tuple: (i32, f64, char) = (42, PI, 'A');
     let tuple_list = tuple.into_tuple_list();
     println!("{:?}", tuple_list);
 
     // Example 2: Creating a tuple list using the macro
-    let tuple_list2 = tuple_list!(1, "hello", 3.14);
+    let tuple_list2 = tuple_list!(1, "hello", PI);
     println!("{:?}", tuple_list2);
     // Example 3 : As Tuple of refs
     let tuple_refs: (&i32, &f64, &char) = tuple.as_tuple_of_refs();
@@ -25,8 +26,15 @@ fn main() {
     println!("Head: {:?}, Tail: {:?}", head, tail);
 
     // Example 5: Utilizing NonEmptyTuple trait methods
-    let non_empty_tuple: (i32, f64, char) = (42, 3.14, 'A');
+    let non_empty_tuple: (i32, f64, char) = (42, PI, 'A');
     let head_value = non_empty_tuple.head();
     let tail_tuple = non_empty_tuple.tail();
     println!("Head Value: {:?}, Tail Tuple: {:?}", head_value, tail_tuple);
+
+    // Example 6: Filling a typestate builder, `build` only compiles once every slot is set
+    let built = Builder::<tuple_list_type!(Missing<i32>, Missing<&str>)>::new()
+        .set("builder")
+        .set(7)
+        .build();
+    println!("Built: {:?}", built);
 }
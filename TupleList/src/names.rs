@@ -0,0 +1,70 @@
+use core::any::type_name;
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::TupleList;
+
+/// Prints the element types of a tuple list, e.g. `[i32, f64, char]` for
+/// `tuple_list_type!(i32, f64, char)`.
+///
+/// Names come from `core::any::type_name`, so non-primitive types are
+/// printed with their full path (`alloc::string::String`).
+pub trait TypeListName: TupleList {
+    fn fmt_items<W: fmt::Write>(f: &mut W, first: bool) -> fmt::Result;
+
+    #[cfg(feature = "std")]
+    fn collect_names(names: &mut Vec<&'static str>);
+
+    fn fmt_types<W: fmt::Write>(f: &mut W) -> fmt::Result {
+        f.write_char('[')?;
+        Self::fmt_items(f, true)?;
+        f.write_char(']')
+    }
+
+    #[cfg(feature = "std")]
+    fn names() -> Vec<&'static str> {
+        let mut names = Vec::with_capacity(Self::TUPLE_LIST_SIZE);
+        Self::collect_names(&mut names);
+        names
+    }
+}
+
+impl TypeListName for () {
+    fn fmt_items<W: fmt::Write>(_: &mut W, _: bool) -> fmt::Result { Ok(()) }
+
+    #[cfg(feature = "std")]
+    fn collect_names(_: &mut Vec<&'static str>) {}
+}
+
+impl<Head, Tail> TypeListName for (Head, Tail) where
+    Tail: TypeListName,
+    Self: TupleList,
+{
+    fn fmt_items<W: fmt::Write>(f: &mut W, first: bool) -> fmt::Result {
+        if !first {
+            f.write_str(", ")?;
+        }
+        f.write_str(type_name::<Head>())?;
+        Tail::fmt_items(f, false)
+    }
+
+    #[cfg(feature = "std")]
+    fn collect_names(names: &mut Vec<&'static str>) {
+        names.push(type_name::<Head>());
+        Tail::collect_names(names);
+    }
+}
+
+/// `Display` adapter over `TypeListName::fmt_types`, handy in log lines and
+/// assertion messages.
+pub struct TypeNames<L>(PhantomData<L>);
+
+pub fn type_names<L: TypeListName>() -> TypeNames<L> {
+    TypeNames(PhantomData)
+}
+
+impl<L: TypeListName> fmt::Display for TypeNames<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        L::fmt_types(f)
+    }
+}
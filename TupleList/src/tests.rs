@@ -1,4 +1,11 @@
-use tuple::{TupleList, Tuple, AsTupleOfRefs, TupleCons, NonEmptyTuple};
+#![allow(clippy::unused_unit, clippy::needless_return, clippy::bool_assert_comparison, clippy::unit_hash)]
+
+use crate::{TupleList, Tuple};
+#[cfg(feature = "std")]
+use crate::{AsTupleOfRefs, TupleCons, NonEmptyTuple};
+use crate::{Builder, Missing, Present};
+use crate::{SplitBorrow, Shared, Mut, Skip};
+use crate::TypeListName;
 
 #[cfg(feature = "std")]
 #[test]
@@ -604,4 +611,190 @@ fn tuple_list_size() {
     assert_eq!(2, <tuple_list_type!(i32, String) as TupleList>::TUPLE_LIST_SIZE);
     #[cfg(feature = "std")]
     assert_eq!(3, <tuple_list_type!(i32, String, bool) as TupleList>::TUPLE_LIST_SIZE);
-}
\ No newline at end of file
+}
+
+#[test]
+fn builder_any_order() {
+    type State = tuple_list_type!(Missing<i32>, Missing<bool>, Missing<&'static str>);
+
+    let built = Builder::<State>::new()
+        .set("abc")
+        .set(1)
+        .set(false)
+        .build();
+    assert_eq!(built, (1, false, "abc"));
+}
+
+#[test]
+fn builder_explicit_slot_type() {
+    let built = Builder::<tuple_list_type!(Missing<u8>, Missing<i64>)>::default()
+        .set::<i64, _>(7)
+        .set::<u8, _>(3)
+        .build();
+    assert_eq!(built, (3u8, 7i64));
+}
+
+#[test]
+fn builder_single_and_empty() {
+    assert_eq!(Builder::<tuple_list_type!(Missing<char>)>::new().set('x').build(), ('x',));
+    assert_eq!(Builder::<tuple_list_type!()>::new().build(), ());
+}
+
+#[test]
+fn builder_partially_present_state() {
+    fn finish<State>(builder: Builder<State>) -> (i32, bool) where
+        State: crate::SetSlot<bool, crate::There<crate::Here>, Output = tuple_list_type!(Present<i32>, Present<bool>)>,
+    {
+        builder.set(true).build()
+    }
+
+    let started = Builder::<tuple_list_type!(Missing<i32>, Missing<bool>)>::new().set(5);
+    assert_eq!(finish(started), (5, true));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn split_borrow_mixed() {
+    fn append(target: &mut String, value: &i32) { target.push_str(&value.to_string()); }
+    fn toggle(flag: &mut bool) { *flag = !*flag; }
+
+    let mut tuple = (1, String::from("abc"), false);
+    let (value, text, flag) = tuple.split_borrow::<(Shared, Mut, Mut)>();
+    append(text, value);
+    toggle(flag);
+    assert_eq!(tuple, (1, String::from("abc1"), true));
+}
+
+#[test]
+fn split_borrow_skip() {
+    let mut tuple = (1, 2, 3);
+    let (a, c) = tuple.split_borrow::<(Mut, Skip, Mut)>();
+    core::mem::swap(a, c);
+    assert_eq!(tuple, (3, 2, 1));
+
+    let () = tuple.split_borrow::<(Skip, Skip, Skip)>();
+    let (b,) = tuple.split_borrow::<(Skip, Shared, Skip)>();
+    assert_eq!(*b, 2);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn type_list_names() {
+    assert_eq!(<tuple_list_type!(i32, f64, char) as TypeListName>::names(), ["i32", "f64", "char"]);
+    assert!(<tuple_list_type!() as TypeListName>::names().is_empty());
+    assert_eq!(
+        format!("{}", crate::type_names::<tuple_list_type!(i32, f64, char)>()),
+        "[i32, f64, char]",
+    );
+    assert_eq!(format!("{}", crate::type_names::<tuple_list_type!()>()), "[]");
+}
+
+#[test]
+fn type_list_fmt_types() {
+    struct Buffer { data: [u8; 32], len: usize }
+
+    impl core::fmt::Write for Buffer {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            let end = self.len + s.len();
+            self.data.get_mut(self.len..end).ok_or(core::fmt::Error)?.copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    let mut buffer = Buffer { data: [0; 32], len: 0 };
+    <tuple_list_type!(u8, bool) as TypeListName>::fmt_types(&mut buffer).unwrap();
+    assert_eq!(&buffer.data[..buffer.len], b"[u8, bool]");
+}
+
+#[cfg(feature = "std")]
+mod futures {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    use crate::{Choice, ChoiceIndex, FutureTuple};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) { self.0.unpark(); }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+                return value;
+            }
+            thread::park();
+        }
+    }
+
+    /// Resolves to `value` after being polled `delay` more times.
+    struct Delayed<T> {
+        delay: usize,
+        value: Option<T>,
+    }
+
+    fn delayed<T>(delay: usize, value: T) -> Delayed<T> {
+        Delayed { delay, value: Some(value) }
+    }
+
+    impl<T: Unpin> Future for Delayed<T> {
+        type Output = T;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+            if self.delay == 0 {
+                return Poll::Ready(self.value.take().unwrap());
+            }
+            self.delay -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn join_all() {
+        let joined = block_on((delayed(3, 1), async { "abc" }, delayed(1, false)).join_all());
+        assert_eq!(joined, (1, "abc", false));
+        assert_eq!(block_on(().join_all()), ());
+    }
+
+    #[test]
+    fn try_join_ok() {
+        let joined = block_on((delayed(2, Ok::<_, String>(1)), async { Ok(true) }).try_join());
+        assert_eq!(joined, Ok((1, true)));
+    }
+
+    #[test]
+    fn try_join_short_circuits() {
+        let never_finishes = delayed(usize::MAX, Ok(0));
+        let failing = delayed(2, Err::<char, _>("boom"));
+        assert_eq!(block_on((never_finishes, failing).try_join()), Err("boom"));
+    }
+
+    #[test]
+    fn select_any() {
+        let selected = block_on((delayed(5, 1), delayed(2, "abc"), delayed(3, false)).select_any());
+        assert_eq!(selected, Choice::Tail(Choice::Head("abc")));
+        assert_eq!(selected.index(), 1);
+
+        let first_wins_ties = block_on((delayed(1, 'a'), delayed(1, 'b')).select_any());
+        assert_eq!(first_wins_ties.index(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "`Join` polled after completion")]
+    fn join_polled_after_completion() {
+        let mut joined = Box::pin((async { 1 },).join_all());
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(joined.as_mut().poll(&mut cx), Poll::Ready((1,)));
+        let _ = joined.as_mut().poll(&mut cx);
+    }
+}
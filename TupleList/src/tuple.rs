@@ -1,6 +1,18 @@
 #![allow(non_snake_case)] 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+mod builder;
+mod borrow;
+mod names;
+#[cfg(feature = "std")]
+mod future;
+
+pub use builder::{Builder, Missing, Present, Here, There, SetSlot, Complete};
+pub use borrow::{SplitBorrow, SplitBorrowed, BorrowSpec, Shared, Mut, Skip};
+pub use names::{TypeListName, TypeNames, type_names};
+#[cfg(feature = "std")]
+pub use future::{FutureTuple, Join, TryJoin, Select, JoinFutures, TryJoinFutures, SelectFutures, MaybeDone, Choice, ChoiceIndex};
+
 pub trait TupleList where Self: Sized {
     type Tuple: Tuple<TupleList=Self>;
     const TUPLE_LIST_SIZE: usize;
@@ -81,7 +93,7 @@ macro_rules! define_tuple_list_traits {
         }
         impl Tuple for () {
             type TupleList = ();
-            fn into_tuple_list(self) -> () { () }
+            fn into_tuple_list(self) {}
         }
         impl<'a> AsTupleOfRefs<'a> for () {
             type TupleOfRefs = ();
@@ -151,4 +163,6 @@ define_tuple_list_traits!(T1, T2, T3, T4, T5, T6, T7, T8);
 define_tuple_list_traits!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
 define_tuple_list_traits!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
 define_tuple_list_traits!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
-define_tuple_list_traits!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
\ No newline at end of file
+define_tuple_list_traits!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,8 @@
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/compile_fail/*.rs");
+    if cfg!(feature = "std") {
+        t.compile_fail("tests/compile_fail/std/*.rs");
+    }
+}
@@ -0,0 +1,9 @@
+use TupleList::{tuple_list_type, Builder, Missing};
+
+fn main() {
+    let _ = Builder::<tuple_list_type!(Missing<i32>, Missing<bool>)>::new()
+        .set(1)
+        .set(2)
+        .set(true)
+        .build();
+}
@@ -0,0 +1,5 @@
+use TupleList::{tuple_list_type, Builder, Missing};
+
+fn main() {
+    let _ = Builder::<tuple_list_type!(Missing<i32>)>::new().set("abc");
+}
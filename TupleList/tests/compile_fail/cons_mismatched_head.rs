@@ -0,0 +1,5 @@
+use TupleList::TupleCons;
+
+fn main() {
+    let _: (i32, bool) = <(bool,) as TupleCons<&str>>::cons("abc", (true,));
+}
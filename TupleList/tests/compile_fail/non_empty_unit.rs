@@ -0,0 +1,9 @@
+use TupleList::NonEmptyTuple;
+
+fn head<T: NonEmptyTuple>(tuple: T) -> T::Head {
+    tuple.head()
+}
+
+fn main() {
+    head(());
+}
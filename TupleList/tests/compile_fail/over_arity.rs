@@ -0,0 +1,5 @@
+use TupleList::Tuple;
+
+fn main() {
+    let _ = (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13).into_tuple_list();
+}
@@ -0,0 +1,8 @@
+use TupleList::{SplitBorrow, Mut, Shared};
+
+fn main() {
+    let mut tuple = (1, 2);
+    let (a, b) = tuple.split_borrow::<(Shared, Mut)>();
+    tuple.0 += 1;
+    *b += *a;
+}
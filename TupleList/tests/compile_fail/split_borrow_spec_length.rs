@@ -0,0 +1,6 @@
+use TupleList::{SplitBorrow, Mut, Shared};
+
+fn main() {
+    let mut tuple = (1, 2, 3);
+    let _ = tuple.split_borrow::<(Shared, Mut)>();
+}
@@ -0,0 +1,5 @@
+use TupleList::FutureTuple;
+
+fn main() {
+    let _ = ().select_any();
+}